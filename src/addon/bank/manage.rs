@@ -173,10 +173,10 @@ pub fn delete_bank(cwd: &str, id: &str) -> Result<(), String> {
 /// - `toml_text`: The TOML content to parse.
 ///
 fn parse_version_from_bank_toml(toml_text: &str) -> Option<String> {
-    if let Ok(doc) = toml::from_str::<BankTomlDoc>(toml_text) {
-        if let Some(b) = doc.bank {
-            return b.version;
-        }
+    if let Ok(doc) = toml::from_str::<BankTomlDoc>(toml_text)
+        && let Some(b) = doc.bank
+    {
+        return b.version;
     }
     None
 }
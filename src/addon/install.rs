@@ -0,0 +1,297 @@
+use crate::utils::{
+    logger::{LogLevel, Logger},
+    path as upath,
+};
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tar::Archive;
+use toml_edit::{ArrayOfTables, DocumentMut, Item, Table, value};
+
+/// Installs a built bank or plugin into a Devalang project.
+///
+/// The archive built in `output/<kind>/` is unpacked into the project's
+/// `.deva/<kind>/<publisher>/<name>/` folder, replacing any previous install, and the
+/// matching `[[banks]]` or `[[plugins]]` entry of `.devalang` is added or updated with
+/// the version of the archive.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `id`: The addon identifier: `<publisher>.<name>`, `bank.<publisher>.<name>` or `plugin.<publisher>.<name>`
+/// - `project`: A directory inside the project; defaults to the current directory
+///
+pub fn install_addon(cwd: &str, id: &str, project: Option<&str>) -> Result<(), String> {
+    let project_root = match project {
+        Some(dir) => {
+            let start = Path::new(cwd).join(dir);
+            if !start.is_dir() {
+                return Err(format!(
+                    "Project directory not found: {}",
+                    start.to_string_lossy()
+                ));
+            }
+            upath::find_project_root_from(&start)
+        }
+        None => upath::find_project_root(),
+    }
+    .ok_or_else(|| {
+        "Devalang project not found (no .devalang or .deva above the project directory). Run 'devalang init' in your project.".to_string()
+    })?;
+
+    let (kind, publisher, name) = resolve_addon_id(cwd, id)?;
+    let archive_path = archive_path(cwd, kind, &publisher, &name);
+    if !archive_path.is_file() {
+        return Err(format!(
+            "Archive not found: {}; run 'devaforge {} build' first",
+            archive_path.to_string_lossy(),
+            kind
+        ));
+    }
+
+    let version = read_archive_version(&archive_path, kind)?;
+
+    // Prepare the config first, so an invalid one stops the install before any file moves.
+    let config_path = project_root.join(upath::DEVALANG_CONFIG);
+    let original = if config_path.exists() {
+        fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read {}: {}", config_path.to_string_lossy(), e))?
+    } else {
+        String::new()
+    };
+    let addon_path = format!("devalang://{}/{}.{}", kind, publisher, name);
+    let updated = upsert_config_entry(&original, &format!("{}s", kind), &addon_path, &version)
+        .map_err(|e| format!("{}: {}", config_path.to_string_lossy(), e))?;
+
+    let install_dir = project_root
+        .join(upath::DEVA_DIR)
+        .join(kind)
+        .join(&publisher)
+        .join(&name);
+    unpack_archive(&archive_path, &install_dir)?;
+
+    if updated != original {
+        fs::write(&config_path, updated)
+            .map_err(|e| format!("Failed to write {}: {}", config_path.to_string_lossy(), e))?;
+    }
+
+    Logger::new().log_message_with_trace(
+        LogLevel::Success,
+        &format!("Installed {} {}.{} v{}", kind, publisher, name, version),
+        vec![
+            &format!("Files: {}", install_dir.to_string_lossy()),
+            &format!("Config: {}", config_path.to_string_lossy()),
+        ],
+    );
+    Ok(())
+}
+
+/// Splits an addon identifier into its kind (`bank` or `plugin`), publisher and name.
+///
+/// Without a `bank.` or `plugin.` prefix, the kind is the one of the only built archive
+/// matching `<publisher>.<name>`.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `id`: The addon identifier
+///
+fn resolve_addon_id(cwd: &str, id: &str) -> Result<(&'static str, String, String), String> {
+    let parts: Vec<&str> = id.split('.').collect();
+    let (kind, publisher, name) = match parts.as_slice() {
+        [kind @ ("bank" | "plugin"), publisher, name] => (Some(*kind), *publisher, *name),
+        [publisher, name] => (None, *publisher, *name),
+        _ => {
+            return Err(format!(
+                "Invalid addon id '{}': expected <publisher>.<name>, bank.<publisher>.<name> or plugin.<publisher>.<name>",
+                id
+            ));
+        }
+    };
+    if publisher.is_empty() || name.is_empty() {
+        return Err(format!("Invalid addon id '{}'", id));
+    }
+
+    let kind = match kind {
+        Some("plugin") => "plugin",
+        Some(_) => "bank",
+        None => {
+            let built: Vec<&'static str> = ["bank", "plugin"]
+                .into_iter()
+                .filter(|k| archive_path(cwd, k, publisher, name).is_file())
+                .collect();
+            match built.as_slice() {
+                [kind] => *kind,
+                [] => {
+                    return Err(format!(
+                        "No built bank or plugin '{}' in {}; run 'devaforge bank build' or 'devaforge plugin build' first",
+                        id,
+                        Path::new(cwd).join("output").to_string_lossy()
+                    ));
+                }
+                _ => {
+                    return Err(format!(
+                        "Both a bank and a plugin are named '{}'; use bank.{} or plugin.{}",
+                        id, id, id
+                    ));
+                }
+            }
+        }
+    };
+    Ok((kind, publisher.to_string(), name.to_string()))
+}
+
+/// Returns the path of the archive built for an addon.
+fn archive_path(cwd: &str, kind: &str, publisher: &str, name: &str) -> PathBuf {
+    Path::new(cwd)
+        .join("output")
+        .join(kind)
+        .join(format!("{}.{}.tar.gz", publisher, name))
+}
+
+/// Reads the addon version from the manifest (`bank.toml` or `plugin.toml`) packed in an archive.
+///
+/// ### Parameters
+/// - `archive_path`: The built archive
+/// - `kind`: The addon kind: `bank` or `plugin`
+///
+fn read_archive_version(archive_path: &Path, kind: &str) -> Result<String, String> {
+    let manifest_name = format!("{}.toml", kind);
+    let file = fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open {}: {}", archive_path.to_string_lossy(), e))?;
+    let mut archive = Archive::new(GzDecoder::new(file));
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read {}: {}", archive_path.to_string_lossy(), e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let is_manifest = entry
+            .path()
+            .map(|p| p == Path::new(&manifest_name))
+            .unwrap_or(false);
+        if !is_manifest {
+            continue;
+        }
+        let mut text = String::new();
+        entry
+            .read_to_string(&mut text)
+            .map_err(|e| format!("Failed to read {}: {}", manifest_name, e))?;
+        let doc: toml::Value =
+            toml::from_str(&text).map_err(|e| format!("Invalid {}: {}", manifest_name, e))?;
+        return doc
+            .get(kind)
+            .and_then(|section| section.get("version"))
+            .and_then(|v| v.as_str())
+            .map(|v| v.to_string())
+            .ok_or_else(|| {
+                format!(
+                    "[{}].version is missing from the packaged {}",
+                    kind, manifest_name
+                )
+            });
+    }
+    Err(format!(
+        "{} not found in {}",
+        manifest_name,
+        archive_path.to_string_lossy()
+    ))
+}
+
+/// Unpacks an archive into a directory, replacing its previous content.
+///
+/// The archive is first unpacked next to the directory, so a failure leaves the
+/// previous install untouched.
+///
+/// ### Parameters
+/// - `archive_path`: The archive to unpack
+/// - `dest`: The install directory
+///
+fn unpack_archive(archive_path: &Path, dest: &Path) -> Result<(), String> {
+    let parent = dest
+        .parent()
+        .ok_or_else(|| format!("Invalid install directory: {}", dest.to_string_lossy()))?;
+    fs::create_dir_all(parent)
+        .map_err(|e| format!("Failed to create {}: {}", parent.to_string_lossy(), e))?;
+    let dir_name = dest
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let staging = parent.join(format!(".{}.installing", dir_name));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to remove {}: {}", staging.to_string_lossy(), e))?;
+    }
+
+    let file = fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open {}: {}", archive_path.to_string_lossy(), e))?;
+    if let Err(e) = Archive::new(GzDecoder::new(file)).unpack(&staging) {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(format!(
+            "Failed to unpack {}: {}",
+            archive_path.to_string_lossy(),
+            e
+        ));
+    }
+
+    if dest.exists() {
+        fs::remove_dir_all(dest)
+            .map_err(|e| format!("Failed to remove {}: {}", dest.to_string_lossy(), e))?;
+    }
+    fs::rename(&staging, dest).map_err(|e| {
+        format!(
+            "Failed to move {} into place: {}",
+            dest.to_string_lossy(),
+            e
+        )
+    })
+}
+
+/// Adds an addon entry to a `.devalang` config, or updates the version of the existing one.
+///
+/// The config is edited as a TOML document, so its comments and layout are kept. New
+/// entries are appended to the array of tables.
+///
+/// ### Parameters
+/// - `original`: The `.devalang` content
+/// - `table`: The array of tables holding the entry: `banks` or `plugins`
+/// - `addon_path`: The entry path, e.g. `devalang://bank/<publisher>.<name>`
+/// - `version`: The installed version
+///
+fn upsert_config_entry(
+    original: &str,
+    table: &str,
+    addon_path: &str,
+    version: &str,
+) -> Result<String, String> {
+    let mut doc: DocumentMut = original
+        .parse()
+        .map_err(|e| format!("Invalid TOML: {}", e))?;
+    let entries = doc
+        .entry(table)
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| format!("'{}' must be an array of tables ([[{}]])", table, table))?;
+
+    let existing = entries
+        .iter_mut()
+        .find(|t| t.get("path").and_then(|v| v.as_str()) == Some(addon_path));
+    match existing {
+        Some(entry) => match entry.get_mut("version").and_then(|v| v.as_value_mut()) {
+            // Replace the value only, keeping its surrounding whitespace and comment.
+            Some(current) => {
+                let decor = current.decor().clone();
+                *current = version.into();
+                *current.decor_mut() = decor;
+            }
+            None => {
+                entry["version"] = value(version);
+            }
+        },
+        None => {
+            let mut entry = Table::new();
+            entry["path"] = value(addon_path);
+            entry["version"] = value(version);
+            entries.push(entry);
+        }
+    }
+    Ok(doc.to_string())
+}
@@ -1,4 +1,5 @@
 pub mod bank;
+pub mod install;
 pub mod plugin;
 pub mod publish;
 pub mod remote_sign;
@@ -125,10 +125,10 @@ pub fn bump_version(cwd: &str, id: &str, bump: &str) -> Result<(), String> {
 }
 
 fn parse_version_from_plugin_toml(toml_text: &str) -> Option<String> {
-    if let Ok(doc) = toml::from_str::<PluginTomlDoc>(toml_text) {
-        if let Some(p) = doc.plugin {
-            return p.version;
-        }
+    if let Ok(doc) = toml::from_str::<PluginTomlDoc>(toml_text)
+        && let Some(p) = doc.plugin
+    {
+        return p.version;
     }
     None
 }
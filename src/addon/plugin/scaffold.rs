@@ -174,29 +174,21 @@ pub async fn add_plugin_to_root_cargo(cwd: &str) -> Result<(), String> {
     let mut newest: Option<(std::time::SystemTime, String)> = None;
     if let Ok(rd) = std::fs::read_dir(&generated_dir) {
         for pub_entry in rd.flatten() {
-            if let Ok(pub_ft) = pub_entry.file_type() {
-                if pub_ft.is_dir() {
-                    let pub_path = pub_entry.path();
-                    if let Ok(child_rd) = std::fs::read_dir(&pub_path) {
-                        for child in child_rd.flatten() {
-                            if let Ok(child_ft) = child.file_type() {
-                                if child_ft.is_dir() {
-                                    if let Ok(m) = child.metadata().and_then(|m| m.modified()) {
-                                        if let (Some(pub_name), Some(child_name)) = (
-                                            pub_entry.file_name().to_str(),
-                                            child.file_name().to_str(),
-                                        ) {
-                                            let rel = format!(
-                                                "generated/plugins/{}/{}",
-                                                pub_name, child_name
-                                            );
-                                            if newest.as_ref().map(|(t, _)| m > *t).unwrap_or(true)
-                                            {
-                                                newest = Some((m, rel));
-                                            }
-                                        }
-                                    }
-                                }
+            if let Ok(pub_ft) = pub_entry.file_type()
+                && pub_ft.is_dir()
+            {
+                let pub_path = pub_entry.path();
+                if let Ok(child_rd) = std::fs::read_dir(&pub_path) {
+                    for child in child_rd.flatten() {
+                        if let Ok(child_ft) = child.file_type()
+                            && child_ft.is_dir()
+                            && let Ok(m) = child.metadata().and_then(|m| m.modified())
+                            && let (Some(pub_name), Some(child_name)) =
+                                (pub_entry.file_name().to_str(), child.file_name().to_str())
+                        {
+                            let rel = format!("generated/plugins/{}/{}", pub_name, child_name);
+                            if newest.as_ref().map(|(t, _)| m > *t).unwrap_or(true) {
+                                newest = Some((m, rel));
                             }
                         }
                     }
@@ -251,34 +243,34 @@ pub async fn add_plugin_to_root_cargo(cwd: &str) -> Result<(), String> {
 
     if section.contains("exclude") {
         // find first '[' and ']' after exclude
-        if let Some(p) = section.find("exclude") {
-            if let Some(o) = section[p..].find('[') {
-                let open = p + o;
-                if let Some(c) = section[open..].find(']') {
-                    let close = open + c;
-                    let inside = &section[open + 1..close];
-                    let mut items: Vec<String> = inside
-                        .split(',')
-                        .map(|s| s.trim().trim_matches('"').to_string())
-                        .filter(|s| !s.is_empty())
-                        .collect();
-                    if items.iter().any(|it| it == &plugin) {
-                        return Ok(());
-                    }
-                    items.push(plugin.clone());
-                    let new_inside = items
-                        .into_iter()
-                        .map(|it| format!("\"{}\"", it))
-                        .collect::<Vec<_>>()
-                        .join(", ");
-                    let old_fragment = &section[open..=close];
-                    let new_fragment = format!("[{}]", new_inside);
-                    let new_section = section.replacen(old_fragment, &new_fragment, 1);
-                    out = orig.replacen(&section, &new_section, 1);
-                    std::fs::write(&cargo_toml_root_path, out)
-                        .map_err(|e| format!("Failed to write root Cargo.toml: {}", e))?;
+        if let Some(p) = section.find("exclude")
+            && let Some(o) = section[p..].find('[')
+        {
+            let open = p + o;
+            if let Some(c) = section[open..].find(']') {
+                let close = open + c;
+                let inside = &section[open + 1..close];
+                let mut items: Vec<String> = inside
+                    .split(',')
+                    .map(|s| s.trim().trim_matches('"').to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect();
+                if items.iter().any(|it| it == &plugin) {
                     return Ok(());
                 }
+                items.push(plugin.clone());
+                let new_inside = items
+                    .into_iter()
+                    .map(|it| format!("\"{}\"", it))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let old_fragment = &section[open..=close];
+                let new_fragment = format!("[{}]", new_inside);
+                let new_section = section.replacen(old_fragment, &new_fragment, 1);
+                out = orig.replacen(&section, &new_section, 1);
+                std::fs::write(&cargo_toml_root_path, out)
+                    .map_err(|e| format!("Failed to write root Cargo.toml: {}", e))?;
+                return Ok(());
             }
         }
     } else {
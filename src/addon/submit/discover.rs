@@ -49,12 +49,11 @@ pub async fn discover_addons() -> Result<Vec<AddonInfo>, String> {
                 }
             };
             let addon_path = addon_entry.path();
-            if addon_path.is_dir() {
-                if let Some(n) = addon_path.file_name().and_then(|s| s.to_str()) {
-                    if !is_ignored_component(n) {
-                        found_subdirs.push(addon_path);
-                    }
-                }
+            if addon_path.is_dir()
+                && let Some(n) = addon_path.file_name().and_then(|s| s.to_str())
+                && !is_ignored_component(n)
+            {
+                found_subdirs.push(addon_path);
             }
         }
 
@@ -97,7 +97,7 @@ pub async fn prompt_submit_addon(cwd: &str) -> Result<(), String> {
     {
         let build_spinner = with_spinner("Building addon before submit...");
         let build_result = match submission_data.addon_type.as_str() {
-            "bank" => bank_builder::build_bank(&submission_data.path, cwd),
+            "bank" => bank_builder::build_bank(&submission_data.path, cwd, false),
             "plugin" =>
             // Align with update flow: do not show summary during submit build
             {
@@ -103,7 +103,7 @@ pub async fn prompt_update_addon(cwd: &str) -> Result<(), String> {
     {
         let build_spinner = with_spinner("Building addon before update...");
         let build_result = match submission_data.addon_type.as_str() {
-            "bank" => bank_builder::build_bank(&submission_data.path, cwd),
+            "bank" => bank_builder::build_bank(&submission_data.path, cwd, false),
             "plugin" => {
                 plugin_builder::build_plugin(&submission_data.path, &false, cwd, false, false)
             }
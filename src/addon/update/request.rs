@@ -88,26 +88,26 @@ pub async fn post_update_addon_to_forge_api(
         let mut tar = TarBuilder::new(enc);
 
         for f in walk_files(&base_path)? {
-            if f.is_file() {
-                if let Some(rel) = path_relative_to(&f, &base_path) {
-                    let skip = rel
-                        .iter()
-                        .any(|comp| comp.to_str().map(is_ignored_component).unwrap_or(false));
-
-                    if skip {
-                        continue;
-                    }
-
-                    // Append file to tar with its relative path (unix separators)
-                    let mut file = std::fs::File::open(&f)
-                        .map_err(|e| format!("Failed to open file '{}': {}", f.display(), e))?;
-                    let mut header_path = rel.to_string_lossy().into_owned();
-                    // Ensure unix separators in tar
-                    header_path = header_path.replace('\\', "/");
-                    tar.append_file(header_path, &mut file).map_err(|e| {
-                        format!("Failed to append file to tar '{}': {}", f.display(), e)
-                    })?;
+            if f.is_file()
+                && let Some(rel) = path_relative_to(&f, &base_path)
+            {
+                let skip = rel
+                    .iter()
+                    .any(|comp| comp.to_str().map(is_ignored_component).unwrap_or(false));
+
+                if skip {
+                    continue;
                 }
+
+                // Append file to tar with its relative path (unix separators)
+                let mut file = std::fs::File::open(&f)
+                    .map_err(|e| format!("Failed to open file '{}': {}", f.display(), e))?;
+                let mut header_path = rel.to_string_lossy().into_owned();
+                // Ensure unix separators in tar
+                header_path = header_path.replace('\\', "/");
+                tar.append_file(header_path, &mut file).map_err(|e| {
+                    format!("Failed to append file to tar '{}': {}", f.display(), e)
+                })?;
             }
         }
 
@@ -127,145 +127,124 @@ pub async fn post_update_addon_to_forge_api(
         // Keep backward compatibility with legacy suffixes
         let cwd_path = crate::utils::fs::get_cwd()?;
         let out_dir = cwd_path.join("output").join(&addon_data.addon_type);
-        if out_dir.exists() && out_dir.is_dir() {
-            if let Ok(entries) = std::fs::read_dir(&out_dir) {
-                for entry in entries.flatten() {
-                    let p = entry.path();
-                    if p.is_file() {
-                        if let Some(fname) = p.file_name().and_then(|s| s.to_str()) {
-                            // Prefer .tar.gz archives, accept legacy names as fallback
-                            if fname.ends_with(".tar.gz")
+        if out_dir.exists()
+            && out_dir.is_dir()
+            && let Ok(entries) = std::fs::read_dir(&out_dir)
+        {
+            for entry in entries.flatten() {
+                let p = entry.path();
+                if p.is_file()
+                    && let Some(fname) = p.file_name().and_then(|s| s.to_str())
+                {
+                    // Prefer .tar.gz archives, accept legacy names as fallback
+                    if (fname.ends_with(".tar.gz")
+                        || fname.ends_with(".devabank.tar.gz")
+                        || fname.ends_with(".devaplugin.tar.gz")
+                        || fname.ends_with(".devabank")
+                        || fname.ends_with(".devaplugin"))
+                        && let Ok(mut f) = std::fs::File::open(&p)
+                    {
+                        let mut file_bytes: Vec<u8> = Vec::new();
+                        if f.read_to_end(&mut file_bytes).is_ok() {
+                            let (raw_buf, gz_buf): (Vec<u8>, Vec<u8>) = if fname
+                                .ends_with(".tar.gz")
+                                || fname.ends_with(".gz")
                                 || fname.ends_with(".devabank.tar.gz")
                                 || fname.ends_with(".devaplugin.tar.gz")
-                                || fname.ends_with(".devabank")
-                                || fname.ends_with(".devaplugin")
                             {
-                                if let Ok(mut f) = std::fs::File::open(&p) {
-                                    let mut file_bytes: Vec<u8> = Vec::new();
-                                    if f.read_to_end(&mut file_bytes).is_ok() {
-                                        let (raw_buf, gz_buf): (Vec<u8>, Vec<u8>) = if fname
-                                            .ends_with(".tar.gz")
-                                            || fname.ends_with(".gz")
-                                            || fname.ends_with(".devabank.tar.gz")
-                                            || fname.ends_with(".devaplugin.tar.gz")
-                                        {
-                                            // file is already gzipped : use file bytes as gz_buf and decompress for raw_buf
-                                            let gz = file_bytes.clone();
-                                            let mut dec = GzDecoder::new(&gz[..]);
-                                            let mut raw = Vec::new();
-                                            if dec.read_to_end(&mut raw).is_err() {
-                                                // fallback: treat file as raw (no decompression)
-                                                (file_bytes.clone(), gz)
-                                            } else {
-                                                (raw, gz)
-                                            }
-                                        } else {
-                                            // file is raw: gzip it
-                                            let mut gz_buf: Vec<u8> = Vec::new();
-                                            let mut enc = GzBuilder::new()
-                                                .mtime(0)
-                                                .write(&mut gz_buf, Compression::default());
-                                            if enc.write_all(&file_bytes).is_err()
-                                                || enc.finish().is_err()
-                                            {
-                                                (file_bytes.clone(), Vec::new())
-                                            } else {
-                                                (file_bytes.clone(), gz_buf)
-                                            }
-                                        };
-
-                                        if gz_buf.is_empty() {
-                                            continue;
-                                        }
+                                // file is already gzipped : use file bytes as gz_buf and decompress for raw_buf
+                                let gz = file_bytes.clone();
+                                let mut dec = GzDecoder::new(&gz[..]);
+                                let mut raw = Vec::new();
+                                if dec.read_to_end(&mut raw).is_err() {
+                                    // fallback: treat file as raw (no decompression)
+                                    (file_bytes.clone(), gz)
+                                } else {
+                                    (raw, gz)
+                                }
+                            } else {
+                                // file is raw: gzip it
+                                let mut gz_buf: Vec<u8> = Vec::new();
+                                let mut enc = GzBuilder::new()
+                                    .mtime(0)
+                                    .write(&mut gz_buf, Compression::default());
+                                if enc.write_all(&file_bytes).is_err() || enc.finish().is_err() {
+                                    (file_bytes.clone(), Vec::new())
+                                } else {
+                                    (file_bytes.clone(), gz_buf)
+                                }
+                            };
 
-                                        // Compute SHA256 of raw archive bytes (before gzip)
-                                        let mut hasher = Sha256::new();
-                                        hasher.update(&raw_buf);
-                                        let sha = hasher.finalize();
-                                        let sha_hex = hex::encode(sha);
-
-                                        // Also compute SHA256 of the gzipped bytes (what we actually send)
-                                        let mut hasher_gz = Sha256::new();
-                                        hasher_gz.update(&gz_buf);
-                                        let sha_gz = hasher_gz.finalize();
-                                        let sha_gz_hex = hex::encode(sha_gz);
-
-                                        // Sign using shared helper (if key exists)
-                                        let (
-                                            signature_b64_opt,
-                                            _signature_gz_b64_opt,
-                                            pubkey_b64_opt,
-                                        ) = crate::addon::self_sign::sign_two_shas(&sha, &sha_gz)
-                                            .unwrap_or_default();
-
-                                        // Attach the archive (gzipped)
-                                        let part = Part::bytes(gz_buf.clone())
-                                            .file_name("archive.tar.gz".to_string());
-                                        form = form.part("files", part);
-
-                                        // Attach signature fields
-                                        if let Some(sig_b64) = signature_b64_opt.clone() {
-                                            form = form.text("signature", sig_b64);
-                                        }
-                                        // gz signature
-                                        if let Ok(home2) = crate::utils::fs::get_user_home() {
-                                            let key_path2 = home2
-                                                .join(".devalang")
-                                                .join("keys")
-                                                .join("ed25519.key");
-                                            if key_path2.exists() {
-                                                if let Ok(bytes2) = std::fs::read(&key_path2) {
-                                                    if bytes2.len() == 64 {
-                                                        if let Ok(kp2) =
-                                                            Keypair::from_bytes(&bytes2)
-                                                        {
-                                                            let sig_gz_b64 =
-                                                                general_purpose::STANDARD.encode(
-                                                                    kp2.sign(&sha_gz).to_bytes(),
-                                                                );
-                                                            form = form
-                                                                .text("signature_gzip", sig_gz_b64);
-                                                        }
-                                                    } else if bytes2.len() == 32 {
-                                                        if let Ok(sk2) =
-                                                            ed25519_dalek::SecretKey::from_bytes(
-                                                                &bytes2,
-                                                            )
-                                                        {
-                                                            let public2 =
-                                                                ed25519_dalek::PublicKey::from(
-                                                                    &sk2,
-                                                                );
-                                                            let kp2 = Keypair {
-                                                                secret: sk2,
-                                                                public: public2,
-                                                            };
-                                                            let sig_gz_b64 =
-                                                                general_purpose::STANDARD.encode(
-                                                                    kp2.sign(&sha_gz).to_bytes(),
-                                                                );
-                                                            form = form
-                                                                .text("signature_gzip", sig_gz_b64);
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        }
+                            if gz_buf.is_empty() {
+                                continue;
+                            }
 
-                                        if let Some(pub_b64) = pubkey_b64_opt.clone() {
-                                            form = form.text("public_key", pub_b64);
+                            // Compute SHA256 of raw archive bytes (before gzip)
+                            let mut hasher = Sha256::new();
+                            hasher.update(&raw_buf);
+                            let sha = hasher.finalize();
+                            let sha_hex = hex::encode(sha);
+
+                            // Also compute SHA256 of the gzipped bytes (what we actually send)
+                            let mut hasher_gz = Sha256::new();
+                            hasher_gz.update(&gz_buf);
+                            let sha_gz = hasher_gz.finalize();
+                            let sha_gz_hex = hex::encode(sha_gz);
+
+                            // Sign using shared helper (if key exists)
+                            let (signature_b64_opt, _signature_gz_b64_opt, pubkey_b64_opt) =
+                                crate::addon::self_sign::sign_two_shas(&sha, &sha_gz)
+                                    .unwrap_or_default();
+
+                            // Attach the archive (gzipped)
+                            let part =
+                                Part::bytes(gz_buf.clone()).file_name("archive.tar.gz".to_string());
+                            form = form.part("files", part);
+
+                            // Attach signature fields
+                            if let Some(sig_b64) = signature_b64_opt.clone() {
+                                form = form.text("signature", sig_b64);
+                            }
+                            // gz signature
+                            if let Ok(home2) = crate::utils::fs::get_user_home() {
+                                let key_path2 =
+                                    home2.join(".devalang").join("keys").join("ed25519.key");
+                                if key_path2.exists()
+                                    && let Ok(bytes2) = std::fs::read(&key_path2)
+                                {
+                                    if bytes2.len() == 64 {
+                                        if let Ok(kp2) = Keypair::from_bytes(&bytes2) {
+                                            let sig_gz_b64 = general_purpose::STANDARD
+                                                .encode(kp2.sign(&sha_gz).to_bytes());
+                                            form = form.text("signature_gzip", sig_gz_b64);
                                         }
-                                        form = form.text("archive_sha256", sha_hex.clone());
-                                        form = form.text("archive_gzip_sha256", sha_gz_hex.clone());
-
-                                        // store to return (raw)
-                                        ret_signature = signature_b64_opt.clone();
-                                        ret_pubkey = pubkey_b64_opt.clone();
-                                        ret_sha = Some(sha_hex.clone());
-                                        break;
+                                    } else if bytes2.len() == 32
+                                        && let Ok(sk2) =
+                                            ed25519_dalek::SecretKey::from_bytes(&bytes2)
+                                    {
+                                        let public2 = ed25519_dalek::PublicKey::from(&sk2);
+                                        let kp2 = Keypair {
+                                            secret: sk2,
+                                            public: public2,
+                                        };
+                                        let sig_gz_b64 = general_purpose::STANDARD
+                                            .encode(kp2.sign(&sha_gz).to_bytes());
+                                        form = form.text("signature_gzip", sig_gz_b64);
                                     }
                                 }
                             }
+
+                            if let Some(pub_b64) = pubkey_b64_opt.clone() {
+                                form = form.text("public_key", pub_b64);
+                            }
+                            form = form.text("archive_sha256", sha_hex.clone());
+                            form = form.text("archive_gzip_sha256", sha_gz_hex.clone());
+
+                            // store to return (raw)
+                            ret_signature = signature_b64_opt.clone();
+                            ret_pubkey = pubkey_b64_opt.clone();
+                            ret_sha = Some(sha_hex.clone());
+                            break;
                         }
                     }
                 }
@@ -301,19 +280,19 @@ pub async fn post_update_addon_to_forge_api(
             }
 
             // If payload.errors is an array, list each as `-> CODE : message`
-            if let Some(payload) = json.get("payload") {
-                if let Some(errors) = payload.get("errors").and_then(|e| e.as_array()) {
-                    for err in errors {
-                        let code = err
-                            .get("code")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("UNKNOWN");
-                        let msg = err.get("message").and_then(|v| v.as_str()).unwrap_or("");
-                        if out.is_empty() {
-                            out.push_str(&format!("{} : {}", code, msg));
-                        } else {
-                            out.push_str(&format!("\n-> {} : {}", code, msg));
-                        }
+            if let Some(payload) = json.get("payload")
+                && let Some(errors) = payload.get("errors").and_then(|e| e.as_array())
+            {
+                for err in errors {
+                    let code = err
+                        .get("code")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("UNKNOWN");
+                    let msg = err.get("message").and_then(|v| v.as_str()).unwrap_or("");
+                    if out.is_empty() {
+                        out.push_str(&format!("{} : {}", code, msg));
+                    } else {
+                        out.push_str(&format!("\n-> {} : {}", code, msg));
                     }
                 }
             }
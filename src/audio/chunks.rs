@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Default MIDI unity note written by most editors, ignored when nothing else is set.
+const DEFAULT_UNITY_NOTE: u8 = 60;
+
+/// Sampler metadata embedded in an audio file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SamplerInfo {
+    /// MIDI note the sample plays at its original pitch
+    pub root: Option<u8>,
+    /// Fine tuning in cents
+    pub tune: Option<i32>,
+    /// Key range `[low, high]`
+    pub keys: Option<[u8; 2]>,
+    /// Velocity range `[low, high]`
+    pub velocity: Option<[u8; 2]>,
+    /// Loop start, in frames
+    pub loop_start: Option<u64>,
+    /// Loop end (exclusive), in frames
+    pub loop_end: Option<u64>,
+}
+
+impl SamplerInfo {
+    fn is_empty(&self) -> bool {
+        *self == SamplerInfo::default()
+    }
+}
+
+/// Reads loop points, root key and mapping ranges from WAV `smpl`/`inst` chunks
+/// or AIFF `MARK`/`INST` chunks.
+///
+/// Returns `None` for other formats and for files without sampler metadata. A
+/// `smpl` chunk that only carries the default unity note (60) and no loop is
+/// ignored, as many editors write one for every file.
+///
+/// ### Parameters
+/// - `path`: The path of the audio file
+///
+pub fn read_sampler_info(path: &Path) -> Result<Option<SamplerInfo>, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.to_string_lossy(), e))?;
+    let mut header = [0u8; 12];
+    if file.read_exact(&mut header).is_err() {
+        return Ok(None);
+    }
+
+    let info = match (&header[0..4], &header[8..12]) {
+        (b"RIFF", b"WAVE") => read_wav_chunks(&mut file),
+        (b"FORM", b"AIFF") | (b"FORM", b"AIFC") => read_aiff_chunks(&mut file),
+        _ => return Ok(None),
+    }
+    .map_err(|e| format!("Failed to read chunks of {}: {}", path.to_string_lossy(), e))?;
+
+    Ok(Some(info).filter(|i| !i.is_empty()))
+}
+
+fn read_wav_chunks(file: &mut fs::File) -> std::io::Result<SamplerInfo> {
+    let mut info = SamplerInfo::default();
+    let mut smpl_root: Option<u8> = None;
+    while let Some((id, size)) = next_chunk(file, u32::from_le_bytes)? {
+        let start = file.stream_position()?;
+        match &id {
+            b"smpl" if size >= 36 => {
+                let data = read_bytes(file, size.min(36 + 24) as usize)?;
+                let le =
+                    |o: usize| u32::from_le_bytes([data[o], data[o + 1], data[o + 2], data[o + 3]]);
+                let unity = le(12);
+                let fraction = le(16);
+                let loops = le(28);
+                if unity <= 127 {
+                    smpl_root = Some(unity as u8);
+                }
+                if fraction != 0 {
+                    // Fraction of a semitone, 0x80000000 = 50 cents.
+                    info.tune = Some(((fraction as f64 / 4_294_967_296.0) * 100.0).round() as i32);
+                }
+                if loops > 0 && data.len() >= 60 {
+                    info.loop_start = Some(le(36 + 8) as u64);
+                    // smpl loop ends are inclusive.
+                    info.loop_end = Some(le(36 + 12) as u64 + 1);
+                }
+            }
+            b"inst" if size >= 7 => {
+                let data = read_bytes(file, 7)?;
+                info.root = Some(data[0]).filter(|n| *n <= 127);
+                if data[1] != 0 {
+                    info.tune = Some(data[1] as i8 as i32);
+                }
+                info.keys = full_range_to_none([data[3], data[4]]);
+                info.velocity = full_range_to_none([data[5].max(1), data[6]]);
+            }
+            _ => {}
+        }
+        file.seek(SeekFrom::Start(start + size as u64 + (size as u64 & 1)))?;
+    }
+
+    if info.root.is_none()
+        && let Some(root) = smpl_root
+        && (root != DEFAULT_UNITY_NOTE || info.loop_start.is_some())
+    {
+        info.root = Some(root);
+    }
+    Ok(info)
+}
+
+fn read_aiff_chunks(file: &mut fs::File) -> std::io::Result<SamplerInfo> {
+    let mut info = SamplerInfo::default();
+    let mut markers: HashMap<i16, u32> = HashMap::new();
+    let mut sustain_loop: Option<(i16, i16)> = None;
+    let file_len = file.metadata()?.len();
+    while let Some((id, size)) = next_chunk(file, u32::from_be_bytes)? {
+        let start = file.stream_position()?;
+        match &id {
+            // A chunk claiming more bytes than the file holds is malformed: skip it.
+            b"MARK" if size >= 2 && size as u64 <= file_len.saturating_sub(start) => {
+                let data = read_bytes(file, size as usize)?;
+                let count = u16::from_be_bytes([data[0], data[1]]);
+                let mut o = 2usize;
+                for _ in 0..count {
+                    if o + 7 > data.len() {
+                        break;
+                    }
+                    let id = i16::from_be_bytes([data[o], data[o + 1]]);
+                    let pos =
+                        u32::from_be_bytes([data[o + 2], data[o + 3], data[o + 4], data[o + 5]]);
+                    markers.insert(id, pos);
+                    // Pascal string padded to an even total length (count byte included).
+                    let name_len = data[o + 6] as usize;
+                    o += 6 + 1 + name_len + ((1 + name_len) & 1);
+                }
+            }
+            b"INST" if size >= 20 => {
+                let data = read_bytes(file, 20)?;
+                info.root = Some(data[0]).filter(|n| *n <= 127);
+                if data[1] != 0 {
+                    info.tune = Some(data[1] as i8 as i32);
+                }
+                info.keys = full_range_to_none([data[2], data[3]]);
+                info.velocity = full_range_to_none([data[4].max(1), data[5]]);
+                let play_mode = i16::from_be_bytes([data[8], data[9]]);
+                if play_mode != 0 {
+                    sustain_loop = Some((
+                        i16::from_be_bytes([data[10], data[11]]),
+                        i16::from_be_bytes([data[12], data[13]]),
+                    ));
+                }
+            }
+            _ => {}
+        }
+        file.seek(SeekFrom::Start(start + size as u64 + (size as u64 & 1)))?;
+    }
+
+    if let Some((begin, end)) = sustain_loop
+        && let (Some(b), Some(e)) = (markers.get(&begin), markers.get(&end))
+        && e > b
+    {
+        info.loop_start = Some(*b as u64);
+        info.loop_end = Some(*e as u64);
+    }
+    Ok(info)
+}
+
+/// Reads the next chunk header, returning its id and size.
+fn next_chunk(
+    file: &mut fs::File,
+    size_from: fn([u8; 4]) -> u32,
+) -> std::io::Result<Option<([u8; 4], u32)>> {
+    let mut header = [0u8; 8];
+    match file.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let id = [header[0], header[1], header[2], header[3]];
+    let size = size_from([header[4], header[5], header[6], header[7]]);
+    Ok(Some((id, size)))
+}
+
+/// Reads `len` bytes, padded with zeros when the file ends first.
+fn read_bytes(file: &mut fs::File, len: usize) -> std::io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(len);
+    file.by_ref().take(len as u64).read_to_end(&mut buf)?;
+    buf.resize(len, 0);
+    Ok(buf)
+}
+
+fn full_range_to_none(range: [u8; 2]) -> Option<[u8; 2]> {
+    let [low, high] = range;
+    if low > high || high > 127 || (low <= 1 && high == 127) {
+        None
+    } else {
+        Some(range)
+    }
+}
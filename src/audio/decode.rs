@@ -0,0 +1,155 @@
+use std::fs;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{CODEC_TYPE_NULL, DecoderOptions};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Audio file extensions that can be decoded.
+pub const SUPPORTED_EXTENSIONS: [&str; 6] = ["wav", "mp3", "ogg", "aif", "aiff", "flac"];
+
+/// Decoded audio content with interleaved `f32` samples.
+#[derive(Debug, Clone, Default)]
+pub struct DecodedAudio {
+    pub sample_rate: u32,
+    pub channels: u16,
+    /// Bit depth of the source encoding, when the codec exposes one (PCM, FLAC).
+    pub bit_depth: Option<u16>,
+    pub samples: Vec<f32>,
+}
+
+impl DecodedAudio {
+    /// Number of frames (samples per channel).
+    pub fn frames(&self) -> usize {
+        if self.channels == 0 {
+            0
+        } else {
+            self.samples.len() / self.channels as usize
+        }
+    }
+
+    /// Duration in seconds.
+    pub fn duration_secs(&self) -> f64 {
+        if self.sample_rate == 0 {
+            0.0
+        } else {
+            self.frames() as f64 / self.sample_rate as f64
+        }
+    }
+}
+
+/// Returns true if the path has an audio extension that can be decoded.
+///
+/// ### Parameters
+/// - `path`: The path of the file
+///
+pub fn is_supported(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| {
+            SUPPORTED_EXTENSIONS
+                .iter()
+                .any(|a| a.eq_ignore_ascii_case(e))
+        })
+        .unwrap_or(false)
+}
+
+/// Decodes a whole audio file into interleaved `f32` samples.
+///
+/// ### Parameters
+/// - `path`: The path of the audio file
+///
+pub fn decode_file(path: &Path) -> Result<DecodedAudio, String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.to_string_lossy(), e))?;
+    let mss = MediaSourceStream::new(Box::new(file), Default::default());
+
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            mss,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("Unsupported audio {}: {}", path.to_string_lossy(), e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| format!("No audio track in {}", path.to_string_lossy()))?;
+    let track_id = track.id;
+    let params = track.codec_params.clone();
+
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&params, &DecoderOptions::default())
+        .map_err(|e| format!("Unsupported codec in {}: {}", path.to_string_lossy(), e))?;
+
+    let mut out = DecodedAudio {
+        sample_rate: params.sample_rate.unwrap_or(0),
+        channels: params.channels.map(|c| c.count() as u16).unwrap_or(0),
+        bit_depth: params.bits_per_sample.map(|b| b as u16),
+        samples: Vec::new(),
+    };
+
+    let mut sample_buf: Option<SampleBuffer<f32>> = None;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(p) => p,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                break;
+            }
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => {
+                return Err(format!("Failed to read {}: {}", path.to_string_lossy(), e));
+            }
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(d) => d,
+            // Corrupt frames are skipped rather than failing the whole file.
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => {
+                return Err(format!(
+                    "Failed to decode {}: {}",
+                    path.to_string_lossy(),
+                    e
+                ));
+            }
+        };
+        let spec = *decoded.spec();
+        if out.sample_rate == 0 {
+            out.sample_rate = spec.rate;
+        }
+        if out.channels == 0 {
+            out.channels = spec.channels.count() as u16;
+        }
+        let buf = sample_buf
+            .get_or_insert_with(|| SampleBuffer::<f32>::new(decoded.capacity() as u64, spec));
+        if buf.capacity() < decoded.capacity() * spec.channels.count() {
+            *buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        }
+        buf.copy_interleaved_ref(decoded);
+        out.samples.extend_from_slice(buf.samples());
+    }
+
+    if out.sample_rate == 0 || out.channels == 0 {
+        return Err(format!(
+            "Could not determine audio format of {}",
+            path.to_string_lossy()
+        ));
+    }
+
+    Ok(out)
+}
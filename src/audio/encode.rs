@@ -0,0 +1,60 @@
+use crate::audio::decode::DecodedAudio;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use std::path::Path;
+
+/// Bit depths accepted when writing WAV files (32 is written as float).
+pub const SUPPORTED_BIT_DEPTHS: [u16; 4] = [8, 16, 24, 32];
+
+/// Writes audio to a WAV file with the given bit depth.
+///
+/// Integer depths are rounded and clipped; 32-bit output is IEEE float.
+///
+/// ### Parameters
+/// - `audio`: The audio to write
+/// - `bit_depth`: The bit depth of the WAV file (8, 16, 24 or 32)
+/// - `out_path`: The path of the WAV file
+///
+pub fn write_wav(audio: &DecodedAudio, bit_depth: u16, out_path: &Path) -> Result<(), String> {
+    if !SUPPORTED_BIT_DEPTHS.contains(&bit_depth) {
+        return Err(format!(
+            "Unsupported bit depth: {} (expected one of 8, 16, 24, 32)",
+            bit_depth
+        ));
+    }
+
+    let spec = WavSpec {
+        channels: audio.channels,
+        sample_rate: audio.sample_rate,
+        bits_per_sample: bit_depth,
+        sample_format: if bit_depth == 32 {
+            SampleFormat::Float
+        } else {
+            SampleFormat::Int
+        },
+    };
+
+    let mut writer = WavWriter::create(out_path, spec)
+        .map_err(|e| format!("Failed to create {}: {}", out_path.to_string_lossy(), e))?;
+    let write_err =
+        |e: hound::Error| format!("Failed to write {}: {}", out_path.to_string_lossy(), e);
+
+    if bit_depth == 32 {
+        for s in &audio.samples {
+            writer.write_sample(*s).map_err(write_err)?;
+        }
+    } else {
+        let max = ((1i64 << (bit_depth - 1)) - 1) as f32;
+        for s in &audio.samples {
+            let v = (s.clamp(-1.0, 1.0) * max).round() as i32;
+            if bit_depth == 8 {
+                writer.write_sample(v as i8).map_err(write_err)?;
+            } else {
+                writer.write_sample(v).map_err(write_err)?;
+            }
+        }
+    }
+
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to finalize {}: {}", out_path.to_string_lossy(), e))
+}
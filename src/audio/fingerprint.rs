@@ -0,0 +1,139 @@
+use crate::audio::decode::DecodedAudio;
+use sha2::{Digest, Sha256};
+
+/// Number of time slices in a fingerprint.
+pub const FINGERPRINT_BINS: usize = 64;
+
+/// Shortest/longest duration ratio under which two samples are never similar.
+const MIN_DURATION_RATIO: f64 = 0.9;
+
+/// Lowest slice level, in dB, so digital silence does not dominate the envelope.
+const ENERGY_FLOOR_DB: f32 = -90.0;
+
+/// Coarse description of a sample, robust to gain changes and lossy re-encoding.
+#[derive(Debug, Clone)]
+pub struct Fingerprint {
+    duration: f64,
+    /// Level of each time slice in dB, relative to the mean level
+    energy: Vec<f32>,
+    /// Zero-crossing rate of each time slice
+    zero_crossings: Vec<f32>,
+}
+
+/// Returns a hash of the decoded audio content.
+///
+/// Files holding the same audio get the same hash whatever their container or
+/// lossless codec (a WAV and its FLAC copy match).
+///
+/// ### Parameters
+/// - `audio`: The decoded audio
+///
+pub fn content_hash(audio: &DecodedAudio) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(audio.sample_rate.to_le_bytes());
+    hasher.update(audio.channels.to_le_bytes());
+    for s in &audio.samples {
+        hasher.update(s.to_le_bytes());
+    }
+    hex::encode(&hasher.finalize()[..16])
+}
+
+/// Computes the fingerprint of decoded audio.
+///
+/// The mono mix is split into [`FINGERPRINT_BINS`] slices, each described by its
+/// level and zero-crossing rate.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio
+///
+pub fn fingerprint(audio: &DecodedAudio) -> Fingerprint {
+    let channels = audio.channels.max(1) as usize;
+    let mono: Vec<f32> = audio
+        .samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+        .collect();
+
+    let mut energy = Vec::with_capacity(FINGERPRINT_BINS);
+    let mut zero_crossings = Vec::with_capacity(FINGERPRINT_BINS);
+    for bin in 0..FINGERPRINT_BINS {
+        let start = mono.len() * bin / FINGERPRINT_BINS;
+        let end = (mono.len() * (bin + 1) / FINGERPRINT_BINS)
+            .max(start + 1)
+            .min(mono.len());
+        let slice = mono.get(start..end).unwrap_or_default();
+        if slice.is_empty() {
+            energy.push(ENERGY_FLOOR_DB);
+            zero_crossings.push(0.0);
+            continue;
+        }
+        let mean_square = slice.iter().map(|s| s * s).sum::<f32>() / slice.len() as f32;
+        energy.push((10.0 * mean_square.max(1e-12).log10()).max(ENERGY_FLOOR_DB));
+        let crossings = slice
+            .windows(2)
+            .filter(|w| (w[0] >= 0.0) != (w[1] >= 0.0))
+            .count();
+        zero_crossings.push(crossings as f32 / slice.len() as f32);
+    }
+
+    let mean = energy.iter().sum::<f32>() / energy.len() as f32;
+    for e in energy.iter_mut() {
+        *e -= mean;
+    }
+
+    Fingerprint {
+        duration: audio.duration_secs(),
+        energy,
+        zero_crossings,
+    }
+}
+
+/// Returns how similar two fingerprints are, from 0 (unrelated) to 1 (identical).
+///
+/// Samples whose durations differ by more than 10% are never similar.
+///
+/// ### Parameters
+/// - `a`: The first fingerprint
+/// - `b`: The second fingerprint
+///
+pub fn similarity(a: &Fingerprint, b: &Fingerprint) -> f64 {
+    let (short, long) = if a.duration < b.duration {
+        (a.duration, b.duration)
+    } else {
+        (b.duration, a.duration)
+    };
+    if long <= 0.0 || short / long < MIN_DURATION_RATIO {
+        return 0.0;
+    }
+
+    // Level envelopes are compared by shape (correlation), so gain does not matter.
+    let dot: f32 = a.energy.iter().zip(&b.energy).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.energy.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.energy.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let envelope = if norm_a == 0.0 || norm_b == 0.0 {
+        if norm_a == norm_b { 1.0 } else { 0.0 }
+    } else {
+        (dot / (norm_a * norm_b)).max(0.0) as f64
+    };
+
+    // Zero-crossing rates roughly follow the spectral content of each slice.
+    let diff: f32 = a
+        .zero_crossings
+        .iter()
+        .zip(&b.zero_crossings)
+        .map(|(x, y)| (x - y).abs())
+        .sum();
+    let total: f32 = a
+        .zero_crossings
+        .iter()
+        .zip(&b.zero_crossings)
+        .map(|(x, y)| x + y)
+        .sum();
+    let spectral = if total == 0.0 {
+        1.0
+    } else {
+        1.0 - (2.0 * diff / total).min(1.0) as f64
+    };
+
+    envelope * spectral
+}
@@ -0,0 +1,141 @@
+use crate::audio::decode::DecodedAudio;
+use crate::audio::resample;
+use std::f64::consts::PI;
+
+/// Lowest rate the audio is decimated to before pitch analysis: blocks of
+/// `sample_rate / ANALYSIS_RATE` samples are averaged, so the analysis runs between
+/// this rate and twice it (12 kHz for 48 kHz audio).
+const ANALYSIS_RATE: u32 = 11025;
+/// Analysis window length, in decimated samples.
+const WINDOW: usize = 4096;
+/// Lowest and highest MIDI notes measured (C2 to B6).
+const NOTE_RANGE: (u8, u8) = (36, 95);
+/// Longest stretch of audio analyzed, in seconds.
+const MAX_ANALYZED_SECS: f64 = 30.0;
+/// Lowest profile correlation for a key to be reported.
+const MIN_CORRELATION: f64 = 0.6;
+
+const PITCH_CLASSES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Krumhansl-Kessler key profiles, from the tonic upwards.
+const MAJOR_PROFILE: [f64; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+const MINOR_PROFILE: [f64; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+
+/// Estimates the musical key of the audio, such as `"A minor"`.
+///
+/// The energy of every note from C2 to B6 is folded into a 12-bin chroma vector,
+/// which is matched against the major and minor key profiles of every tonic.
+/// Returns `None` when no key fits clearly, which is the case of most drum loops.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio
+///
+pub fn estimate_key(audio: &DecodedAudio) -> Option<String> {
+    let chroma = chroma(audio)?;
+
+    let mut best: Option<(f64, usize, bool)> = None;
+    for tonic in 0..12 {
+        for (minor, profile) in [(false, &MAJOR_PROFILE), (true, &MINOR_PROFILE)] {
+            let rotated: Vec<f64> = (0..12).map(|pc| profile[(pc + 12 - tonic) % 12]).collect();
+            let r = correlation(&chroma, &rotated);
+            if best.is_none_or(|(b, _, _)| r > b) {
+                best = Some((r, tonic, minor));
+            }
+        }
+    }
+
+    let (r, tonic, minor) = best?;
+    if r < MIN_CORRELATION {
+        return None;
+    }
+    Some(format!(
+        "{} {}",
+        PITCH_CLASSES[tonic],
+        if minor { "minor" } else { "major" }
+    ))
+}
+
+/// Sums the magnitude of every measured note per pitch class, over Hann-windowed frames.
+fn chroma(audio: &DecodedAudio) -> Option<[f64; 12]> {
+    let mut mono = resample::remix(audio, 1);
+    mono.samples
+        .truncate((audio.sample_rate as f64 * MAX_ANALYZED_SECS) as usize);
+
+    // Decimate by averaging blocks of samples, without resampling. The measured notes
+    // (up to B6, about 2 kHz) lose less than 0.5 dB, but the block average is a weak
+    // anti-alias filter: content that folds onto them is only attenuated by 13 dB or
+    // more. That slightly blurs the chroma, which the profile correlation tolerates.
+    let factor = (audio.sample_rate / ANALYSIS_RATE).max(1) as usize;
+    let rate = audio.sample_rate as f64 / factor as f64;
+    let samples: Vec<f64> = mono
+        .samples
+        .chunks_exact(factor)
+        .map(|block| block.iter().map(|s| *s as f64).sum::<f64>() / factor as f64)
+        .collect();
+    if samples.len() < WINDOW {
+        return None;
+    }
+
+    let window: Vec<f64> = (0..WINDOW)
+        .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / (WINDOW - 1) as f64).cos())
+        .collect();
+    let coefficients: Vec<(usize, f64)> = (NOTE_RANGE.0..=NOTE_RANGE.1)
+        .map(|note| {
+            let freq = 440.0 * 2f64.powf((note as f64 - 69.0) / 12.0);
+            (note as usize % 12, 2.0 * (2.0 * PI * freq / rate).cos())
+        })
+        .collect();
+
+    let mut chroma = [0.0f64; 12];
+    let mut start = 0;
+    let mut frame = vec![0.0f64; WINDOW];
+    while start + WINDOW <= samples.len() {
+        for (i, x) in frame.iter_mut().enumerate() {
+            *x = samples[start + i] * window[i];
+        }
+        // Goertzel filter per note.
+        for (pc, coeff) in &coefficients {
+            let (mut s1, mut s2) = (0.0f64, 0.0f64);
+            for x in &frame {
+                let s0 = x + coeff * s1 - s2;
+                s2 = s1;
+                s1 = s0;
+            }
+            let power = (s1 * s1 + s2 * s2 - coeff * s1 * s2).max(0.0);
+            chroma[*pc] += power.sqrt();
+        }
+        start += WINDOW / 2;
+    }
+
+    if chroma.iter().all(|c| *c == 0.0) {
+        None
+    } else {
+        Some(chroma)
+    }
+}
+
+/// Pearson correlation of two vectors of the same length.
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let mut cov = 0.0;
+    let mut var_a = 0.0;
+    let mut var_b = 0.0;
+    for (x, y) in a.iter().zip(b) {
+        cov += (x - mean_a) * (y - mean_b);
+        var_a += (x - mean_a) * (x - mean_a);
+        var_b += (y - mean_b) * (y - mean_b);
+    }
+    if var_a == 0.0 || var_b == 0.0 {
+        0.0
+    } else {
+        cov / (var_a * var_b).sqrt()
+    }
+}
@@ -0,0 +1,116 @@
+use crate::audio::decode::DecodedAudio;
+use std::f64::consts::PI;
+
+/// Absolute gating threshold of ITU-R BS.1770, in LUFS.
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+/// Relative gating threshold of ITU-R BS.1770, in LU below the ungated level.
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Measures the integrated loudness of the audio in LUFS (ITU-R BS.1770).
+///
+/// Samples shorter than one 400 ms block are measured as a single block, which
+/// suits one-shots. Returns `None` for silent audio.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio to measure
+///
+pub fn integrated_loudness(audio: &DecodedAudio) -> Option<f64> {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.frames();
+    if frames == 0 || audio.sample_rate == 0 {
+        return None;
+    }
+
+    // K-weighted power per frame, summed over channels.
+    let mut power = vec![0.0f64; frames];
+    for c in 0..channels {
+        let mut shelf = Biquad::high_shelf(audio.sample_rate as f64);
+        let mut high_pass = Biquad::high_pass(audio.sample_rate as f64);
+        for (f, p) in power.iter_mut().enumerate() {
+            let x = audio.samples[f * channels + c] as f64;
+            let y = high_pass.process(shelf.process(x));
+            *p += y * y;
+        }
+    }
+
+    let block = (audio.sample_rate as f64 * 0.4) as usize;
+    let step = (block / 4).max(1);
+    let mut blocks: Vec<f64> = Vec::new();
+    if frames < block {
+        blocks.push(power.iter().sum::<f64>() / frames as f64);
+    } else {
+        let mut start = 0;
+        while start + block <= frames {
+            blocks.push(power[start..start + block].iter().sum::<f64>() / block as f64);
+            start += step;
+        }
+    }
+
+    let loudness = |ms: f64| -0.691 + 10.0 * ms.log10();
+    let gated_mean = |threshold: f64| -> Option<f64> {
+        let kept: Vec<f64> = blocks
+            .iter()
+            .cloned()
+            .filter(|ms| *ms > 0.0 && loudness(*ms) > threshold)
+            .collect();
+        if kept.is_empty() {
+            None
+        } else {
+            Some(kept.iter().sum::<f64>() / kept.len() as f64)
+        }
+    };
+
+    let ungated = gated_mean(ABSOLUTE_GATE_LUFS)?;
+    let relative = loudness(ungated) + RELATIVE_GATE_LU;
+    let gated = gated_mean(relative.max(ABSOLUTE_GATE_LUFS))?;
+    Some(loudness(gated))
+}
+
+/// Second-order IIR section used for the K-weighting pre-filter.
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    /// High shelf of the K-weighting filter (+4 dB above ~1.5 kHz).
+    fn high_shelf(rate: f64) -> Self {
+        let gain_db = 3.999_843_853_973_347;
+        let q = 0.707_175_236_955_419_6;
+        let fc = 1_681.974_450_955_533;
+        let k = (PI * fc / rate).tan();
+        let vh = 10f64.powf(gain_db / 20.0);
+        let vb = vh.powf(0.499_666_774_154_541_6);
+        let a0 = 1.0 + k / q + k * k;
+        Biquad {
+            b: [
+                (vh + vb * k / q + k * k) / a0,
+                2.0 * (k * k - vh) / a0,
+                (vh - vb * k / q + k * k) / a0,
+            ],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        }
+    }
+
+    /// High pass of the K-weighting filter (~38 Hz).
+    fn high_pass(rate: f64) -> Self {
+        let q = 0.500_327_037_323_877_3;
+        let fc = 38.135_470_876_024_44;
+        let k = (PI * fc / rate).tan();
+        let a0 = 1.0 + k / q + k * k;
+        Biquad {
+            b: [1.0, -2.0, 1.0],
+            a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            z: [0.0; 2],
+        }
+    }
+
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
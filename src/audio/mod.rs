@@ -0,0 +1,12 @@
+pub mod chunks;
+pub mod decode;
+pub mod encode;
+pub mod fingerprint;
+pub mod key;
+pub mod loudness;
+pub mod probe;
+pub mod process;
+pub mod resample;
+pub mod signal;
+pub mod tempo;
+pub mod waveform;
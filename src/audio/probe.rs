@@ -0,0 +1,70 @@
+use crate::audio::decode::{self, DecodedAudio};
+use crate::audio::fingerprint;
+use std::path::Path;
+
+/// Lowest level reported for a peak, used for digital silence.
+pub const SILENCE_FLOOR_DB: f64 = -120.0;
+
+/// Audio properties of a decoded sample.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AudioProbe {
+    /// Duration in seconds, rounded to the millisecond.
+    pub duration: f64,
+    pub sample_rate: u32,
+    pub bit_depth: Option<u16>,
+    pub channels: u16,
+    /// Sample peak in dBFS, rounded to 0.01 dB.
+    pub peak_db: f64,
+    /// Hash of the decoded audio content.
+    pub hash: String,
+}
+
+/// Decodes an audio file and measures its properties.
+///
+/// ### Parameters
+/// - `path`: The path of the audio file
+///
+pub fn probe_file(path: &Path) -> Result<AudioProbe, String> {
+    let audio = decode::decode_file(path)?;
+    Ok(probe_decoded(&audio))
+}
+
+/// Measures the properties of already decoded audio.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio
+///
+pub fn probe_decoded(audio: &DecodedAudio) -> AudioProbe {
+    AudioProbe {
+        duration: round_to(audio.duration_secs(), 3),
+        sample_rate: audio.sample_rate,
+        bit_depth: audio.bit_depth,
+        channels: audio.channels,
+        peak_db: round_to(peak_db(&audio.samples), 2),
+        hash: fingerprint::content_hash(audio),
+    }
+}
+
+/// Returns the absolute sample peak in dBFS, floored at [`SILENCE_FLOOR_DB`].
+///
+/// ### Parameters
+/// - `samples`: The samples to measure
+///
+pub fn peak_db(samples: &[f32]) -> f64 {
+    let peak = samples.iter().fold(0.0f32, |acc, s| acc.max(s.abs())) as f64;
+    if peak <= 0.0 {
+        return SILENCE_FLOOR_DB;
+    }
+    (20.0 * peak.log10()).max(SILENCE_FLOOR_DB)
+}
+
+/// Rounds a value to the given number of decimals.
+///
+/// ### Parameters
+/// - `value`: The value to round
+/// - `decimals`: The number of decimals to keep
+///
+pub fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
@@ -0,0 +1,104 @@
+use crate::audio::{decode::DecodedAudio, loudness, probe};
+
+/// Length of the fade applied after trimming trailing silence, in seconds.
+const TRIM_FADE_SECS: f64 = 0.005;
+/// Highest peak allowed after loudness normalization, in dBFS.
+const LOUDNESS_PEAK_CEILING_DB: f64 = 0.0;
+
+/// Removes leading and trailing frames whose level stays below the threshold.
+///
+/// Leading silence is cut right before the first frame above the threshold so
+/// transients land on the first sample. A short fade-out is applied when the tail
+/// is cut to avoid clicks. Audio that never exceeds the threshold is left as is.
+/// Returns the trimmed audio and the number of leading frames removed.
+///
+/// ### Parameters
+/// - `audio`: The audio to trim
+/// - `threshold_db`: The silence threshold in dBFS
+///
+pub fn trim_silence(audio: &DecodedAudio, threshold_db: f64) -> (DecodedAudio, usize) {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.frames();
+    let threshold = db_to_gain(threshold_db) as f32;
+    let loud = |f: usize| {
+        audio.samples[f * channels..(f + 1) * channels]
+            .iter()
+            .any(|s| s.abs() > threshold)
+    };
+
+    let Some(first) = (0..frames).find(|f| loud(*f)) else {
+        return (audio.clone(), 0);
+    };
+    let last = (0..frames).rev().find(|f| loud(*f)).unwrap_or(first);
+
+    let mut out = audio.clone();
+    out.samples = audio.samples[first * channels..(last + 1) * channels].to_vec();
+
+    if last + 1 < frames {
+        let fade = ((audio.sample_rate as f64 * TRIM_FADE_SECS) as usize).min(out.frames());
+        let total = out.frames();
+        for i in 0..fade {
+            let gain = (fade - i) as f32 / fade as f32;
+            let f = total - fade + i;
+            for c in 0..channels {
+                out.samples[f * channels + c] *= gain;
+            }
+        }
+    }
+    (out, first)
+}
+
+/// Scales the audio so its sample peak reaches the target level.
+///
+/// ### Parameters
+/// - `audio`: The audio to normalize
+/// - `target_db`: The target peak in dBFS
+///
+pub fn normalize_peak(audio: &DecodedAudio, target_db: f64) -> DecodedAudio {
+    let peak = probe::peak_db(&audio.samples);
+    if peak <= probe::SILENCE_FLOOR_DB {
+        return audio.clone();
+    }
+    apply_gain(audio, target_db - peak)
+}
+
+/// Scales the audio so its integrated loudness reaches the target level.
+///
+/// The gain is limited so the sample peak never exceeds 0 dBFS.
+///
+/// ### Parameters
+/// - `audio`: The audio to normalize
+/// - `target_lufs`: The target loudness in LUFS
+///
+pub fn normalize_loudness(audio: &DecodedAudio, target_lufs: f64) -> DecodedAudio {
+    let Some(current) = loudness::integrated_loudness(audio) else {
+        return audio.clone();
+    };
+    let peak = probe::peak_db(&audio.samples);
+    let gain_db = (target_lufs - current).min(LOUDNESS_PEAK_CEILING_DB - peak);
+    apply_gain(audio, gain_db)
+}
+
+/// Applies a gain in dB to every sample.
+///
+/// ### Parameters
+/// - `audio`: The audio to scale
+/// - `gain_db`: The gain in dB
+///
+pub fn apply_gain(audio: &DecodedAudio, gain_db: f64) -> DecodedAudio {
+    let gain = db_to_gain(gain_db) as f32;
+    let mut out = audio.clone();
+    for s in out.samples.iter_mut() {
+        *s *= gain;
+    }
+    out
+}
+
+/// Converts decibels to a linear gain.
+///
+/// ### Parameters
+/// - `db`: The level in dB
+///
+pub fn db_to_gain(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
@@ -0,0 +1,118 @@
+use crate::audio::decode::DecodedAudio;
+use std::f64::consts::PI;
+
+/// Half-width of the windowed-sinc kernel, in input samples at unity ratio.
+const SINC_HALF_WIDTH: usize = 32;
+
+/// Resamples interleaved audio to the given sample rate using a windowed-sinc filter.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio to resample
+/// - `target_rate`: The target sample rate
+///
+pub fn resample(audio: &DecodedAudio, target_rate: u32) -> DecodedAudio {
+    if audio.sample_rate == target_rate || audio.sample_rate == 0 || target_rate == 0 {
+        let mut out = audio.clone();
+        out.sample_rate = if target_rate == 0 {
+            audio.sample_rate
+        } else {
+            target_rate
+        };
+        return out;
+    }
+
+    let channels = audio.channels.max(1) as usize;
+    let in_frames = audio.frames();
+    let ratio = target_rate as f64 / audio.sample_rate as f64;
+    let out_frames = (in_frames as f64 * ratio).round() as usize;
+
+    // When downsampling, lower the cutoff and widen the kernel to avoid aliasing.
+    let cutoff = ratio.min(1.0);
+    let half_width = (SINC_HALF_WIDTH as f64 / cutoff).ceil() as isize;
+
+    let mut samples = vec![0.0f32; out_frames * channels];
+    for i in 0..out_frames {
+        let t = i as f64 / ratio;
+        let center = t.floor() as isize;
+        let start = (center - half_width + 1).max(0);
+        let end = (center + half_width).min(in_frames as isize - 1);
+        let mut acc = vec![0.0f64; channels];
+        let mut norm = 0.0f64;
+        for k in start..=end {
+            let x = t - k as f64;
+            let w = sinc(x * cutoff) * blackman(x / half_width as f64);
+            norm += w;
+            let base = k as usize * channels;
+            for (c, a) in acc.iter_mut().enumerate() {
+                *a += audio.samples[base + c] as f64 * w;
+            }
+        }
+        if norm.abs() > f64::EPSILON {
+            let base = i * channels;
+            for (c, a) in acc.iter().enumerate() {
+                samples[base + c] = (a / norm) as f32;
+            }
+        }
+    }
+
+    DecodedAudio {
+        sample_rate: target_rate,
+        channels: audio.channels,
+        bit_depth: audio.bit_depth,
+        samples,
+    }
+}
+
+/// Converts interleaved audio to the given channel count.
+///
+/// Downmixing to mono averages all channels, upmixing from mono duplicates the
+/// signal, and any other conversion keeps or repeats the leading channels.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio to convert
+/// - `target_channels`: The target channel count
+///
+pub fn remix(audio: &DecodedAudio, target_channels: u16) -> DecodedAudio {
+    let src = audio.channels.max(1) as usize;
+    let dst = target_channels.max(1) as usize;
+    if src == dst {
+        return audio.clone();
+    }
+
+    let frames = audio.frames();
+    let mut samples = Vec::with_capacity(frames * dst);
+    for f in 0..frames {
+        let frame = &audio.samples[f * src..f * src + src];
+        if dst == 1 {
+            samples.push(frame.iter().sum::<f32>() / src as f32);
+        } else if src == 1 {
+            samples.extend(std::iter::repeat_n(frame[0], dst));
+        } else {
+            samples.extend((0..dst).map(|c| frame[c % src]));
+        }
+    }
+
+    DecodedAudio {
+        sample_rate: audio.sample_rate,
+        channels: dst as u16,
+        bit_depth: audio.bit_depth,
+        samples,
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-9 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+fn blackman(x: f64) -> f64 {
+    // x in [-1, 1]
+    if x.abs() >= 1.0 {
+        return 0.0;
+    }
+    let n = (x + 1.0) / 2.0;
+    0.42 - 0.5 * (2.0 * PI * n).cos() + 0.08 * (4.0 * PI * n).cos()
+}
@@ -0,0 +1,100 @@
+use crate::audio::decode::DecodedAudio;
+use std::f64::consts::PI;
+
+/// Test signals accepted by [`generate`].
+pub const SIGNALS: [&str; 4] = ["sine", "sweep", "noise", "impulse"];
+
+/// Level of the generated signals (-6 dBFS), leaving headroom for gain.
+const AMPLITUDE: f64 = 0.5;
+
+/// Start frequency of the sweep, in Hz.
+const SWEEP_START: f64 = 20.0;
+
+/// Generates a mono test signal.
+///
+/// - `sine`: a sine at `freq` Hz
+/// - `sweep`: a logarithmic sine sweep from 20 Hz to `freq` Hz
+/// - `noise`: white noise, identical on every run
+/// - `impulse`: a single full-scale sample followed by silence
+///
+/// ### Parameters
+/// - `kind`: The signal, one of [`SIGNALS`]
+/// - `freq`: The frequency in Hz (end frequency of the sweep, above 20 Hz)
+/// - `duration`: The duration in seconds
+/// - `sample_rate`: The sample rate in Hz
+///
+pub fn generate(
+    kind: &str,
+    freq: f64,
+    duration: f64,
+    sample_rate: u32,
+) -> Result<DecodedAudio, String> {
+    if duration.is_nan() || duration <= 0.0 {
+        return Err(format!("Invalid duration: {} (must be > 0)", duration));
+    }
+    if sample_rate == 0 {
+        return Err("Invalid sample rate: 0".to_string());
+    }
+    let nyquist = sample_rate as f64 / 2.0;
+    if !(freq > 0.0 && freq < nyquist) {
+        return Err(format!(
+            "Invalid frequency: {} Hz (expected 0..{} Hz)",
+            freq, nyquist
+        ));
+    }
+
+    let frames = (duration * sample_rate as f64).round() as usize;
+    let sr = sample_rate as f64;
+    let samples: Vec<f32> = match kind {
+        "sine" => (0..frames)
+            .map(|i| (AMPLITUDE * (2.0 * PI * freq * i as f64 / sr).sin()) as f32)
+            .collect(),
+        "sweep" => {
+            if freq <= SWEEP_START {
+                return Err(format!(
+                    "Invalid frequency: {} Hz (sweep end frequency must be above {} Hz)",
+                    freq, SWEEP_START
+                ));
+            }
+            let start = SWEEP_START;
+            let ratio = (freq / start).ln();
+            (0..frames)
+                .map(|i| {
+                    let t = i as f64 / sr;
+                    let phase =
+                        2.0 * PI * start * duration / ratio * ((t / duration * ratio).exp() - 1.0);
+                    (AMPLITUDE * phase.sin()) as f32
+                })
+                .collect()
+        }
+        "noise" => {
+            let mut state: u32 = 0x1234_5678;
+            (0..frames)
+                .map(|_| {
+                    // xorshift32
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    (AMPLITUDE * (state as f64 / u32::MAX as f64 * 2.0 - 1.0)) as f32
+                })
+                .collect()
+        }
+        "impulse" => (0..frames)
+            .map(|i| if i == 0 { 1.0 } else { 0.0 })
+            .collect(),
+        other => {
+            return Err(format!(
+                "Unknown signal: {} (expected: {})",
+                other,
+                SIGNALS.join("|")
+            ));
+        }
+    };
+
+    Ok(DecodedAudio {
+        sample_rate,
+        channels: 1,
+        bit_depth: None,
+        samples,
+    })
+}
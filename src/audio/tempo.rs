@@ -0,0 +1,152 @@
+use crate::audio::decode::DecodedAudio;
+use crate::audio::probe::round_to;
+
+/// Slowest tempo considered, in BPM.
+const MIN_BPM: f64 = 60.0;
+/// Fastest tempo considered, in BPM.
+const MAX_BPM: f64 = 200.0;
+/// Hop between onset envelope points, in seconds.
+const HOP_SECS: f64 = 0.01;
+/// Longest stretch of audio analyzed, in seconds.
+const MAX_ANALYZED_SECS: f64 = 60.0;
+/// Share of the best free tempo score a whole-beat loop tempo must reach to win.
+const LOOP_FIT_RATIO: f64 = 0.85;
+
+/// Estimates the tempo of a loop in BPM.
+///
+/// Onsets are tracked through the rises of the signal energy, and tempos are scored
+/// by the autocorrelation of that envelope at one beat, two beats and one bar.
+/// Tempos that make the loop last a whole number of beats are preferred when they
+/// score close to the best tempo overall. Returns `None` for audio too short or
+/// without any rhythmic content.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio
+///
+pub fn estimate_bpm(audio: &DecodedAudio) -> Option<f64> {
+    let duration = audio.duration_secs();
+    let envelope = onset_envelope(audio);
+    if duration < 60.0 / MAX_BPM * 2.0 || envelope.len() < 16 {
+        return None;
+    }
+    let ac = autocorrelation(&envelope);
+
+    let mut best_free = (0.0, 0.0);
+    let mut bpm = MIN_BPM;
+    while bpm <= MAX_BPM {
+        let score = tempo_score(&ac, bpm);
+        if score > best_free.1 {
+            best_free = (bpm, score);
+        }
+        bpm += 0.5;
+    }
+    if best_free.1 <= 0.0 {
+        return None;
+    }
+
+    // Loops usually hold a whole number of beats, often a multiple of a bar.
+    let mut best_loop = (0.0, 0.0);
+    for beats in 1..=256u32 {
+        let bpm = 60.0 * beats as f64 / duration;
+        if !(MIN_BPM..=MAX_BPM).contains(&bpm) {
+            continue;
+        }
+        let bonus = if beats % 4 == 0 { 1.1 } else { 1.0 };
+        let score = tempo_score(&ac, bpm) * bonus;
+        if score > best_loop.1 {
+            best_loop = (bpm, score);
+        }
+    }
+
+    if best_loop.1 >= best_free.1 * LOOP_FIT_RATIO {
+        Some(round_to(best_loop.0, 2))
+    } else {
+        Some(round_to(best_free.0, 1))
+    }
+}
+
+/// Returns the number of beats the audio lasts at the given tempo.
+///
+/// ### Parameters
+/// - `duration`: The duration in seconds
+/// - `bpm`: The tempo in BPM
+///
+pub fn beats(duration: f64, bpm: f64) -> f64 {
+    duration * bpm / 60.0
+}
+
+/// Computes the onset strength envelope: the rises of the log energy per hop.
+fn onset_envelope(audio: &DecodedAudio) -> Vec<f64> {
+    let channels = audio.channels.max(1) as usize;
+    let hop = ((audio.sample_rate as f64 * HOP_SECS) as usize).max(1);
+    let frames = audio
+        .frames()
+        .min((audio.sample_rate as f64 * MAX_ANALYZED_SECS) as usize);
+
+    // Pre-emphasis (first difference) so hats and snares weigh like kicks.
+    let mut energies = Vec::with_capacity(frames / hop + 1);
+    let mut prev = 0.0f64;
+    let mut acc = 0.0f64;
+    for f in 0..frames {
+        let x = audio.samples[f * channels..(f + 1) * channels]
+            .iter()
+            .map(|s| *s as f64)
+            .sum::<f64>()
+            / channels as f64;
+        let d = x - prev;
+        prev = x;
+        acc += x * x + d * d;
+        if (f + 1) % hop == 0 {
+            energies.push((1.0 + 1000.0 * acc / hop as f64).ln());
+            acc = 0.0;
+        }
+    }
+
+    energies
+        .windows(2)
+        .map(|w| (w[1] - w[0]).max(0.0))
+        .collect()
+}
+
+/// Normalized autocorrelation of the mean-removed envelope, for every lag up to half its length.
+fn autocorrelation(envelope: &[f64]) -> Vec<f64> {
+    let mean = envelope.iter().sum::<f64>() / envelope.len() as f64;
+    let x: Vec<f64> = envelope.iter().map(|v| v - mean).collect();
+    let energy = x.iter().map(|v| v * v).sum::<f64>() / x.len() as f64;
+    if energy == 0.0 {
+        return vec![0.0; x.len() / 2];
+    }
+    (0..x.len() / 2)
+        .map(|lag| {
+            let n = x.len() - lag;
+            let sum: f64 = x[..n].iter().zip(&x[lag..]).map(|(a, b)| a * b).sum();
+            sum / n as f64 / energy
+        })
+        .collect()
+}
+
+/// Scores a tempo by the periodicity of the envelope at one beat, two beats and one bar,
+/// weighted towards common tempos.
+fn tempo_score(ac: &[f64], bpm: f64) -> f64 {
+    let period = 60.0 / bpm / HOP_SECS;
+    let at = |lag: f64| -> Option<f64> {
+        let i = lag.floor() as usize;
+        if i + 1 >= ac.len() {
+            return None;
+        }
+        let t = lag - i as f64;
+        Some(ac[i] * (1.0 - t) + ac[i + 1] * t)
+    };
+    let Some(beat) = at(period) else {
+        return 0.0;
+    };
+    let mut score = beat;
+    if let Some(two) = at(period * 2.0) {
+        score += 0.5 * two;
+    }
+    if let Some(bar) = at(period * 4.0) {
+        score += 0.25 * bar;
+    }
+    let octaves = (bpm / 120.0).log2();
+    score.max(0.0) * (-0.5 * octaves * octaves).exp()
+}
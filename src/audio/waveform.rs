@@ -0,0 +1,55 @@
+use crate::audio::decode::DecodedAudio;
+
+/// Default width of a waveform preview, in pixels.
+pub const PREVIEW_WIDTH: usize = 400;
+/// Default height of a waveform preview, in pixels.
+pub const PREVIEW_HEIGHT: usize = 64;
+
+/// Renders a min/max waveform of the audio as a standalone SVG document.
+///
+/// All channels are folded into a single lane; each column shows the lowest and
+/// highest sample of its slice of the audio.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio
+/// - `width`: The width of the image in pixels
+/// - `height`: The height of the image in pixels
+///
+pub fn render_svg(audio: &DecodedAudio, width: usize, height: usize) -> String {
+    let channels = audio.channels.max(1) as usize;
+    let frames = audio.frames();
+    let width = width.max(1);
+    let mid = height as f32 / 2.0;
+
+    let mut top: Vec<String> = Vec::with_capacity(width);
+    let mut bottom: Vec<String> = Vec::with_capacity(width);
+    for x in 0..width {
+        let start = x * frames / width;
+        let end = ((x + 1) * frames / width).max(start + 1).min(frames);
+        let (mut lo, mut hi) = (0.0f32, 0.0f32);
+        if start < frames {
+            for s in &audio.samples[start * channels..end * channels] {
+                lo = lo.min(*s);
+                hi = hi.max(*s);
+            }
+        }
+        let y_hi = mid - hi.clamp(-1.0, 1.0) * mid;
+        let y_lo = mid - lo.clamp(-1.0, 1.0) * mid;
+        top.push(format!("{},{:.1}", x, y_hi));
+        bottom.push(format!("{},{:.1}", x, y_lo));
+    }
+    bottom.reverse();
+
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n<line x1=\"0\" y1=\"{m}\" x2=\"{w}\" y2=\"{m}\" stroke=\"#1dd3b0\" stroke-opacity=\"0.3\"/>\n<polygon points=\"{pts}\" fill=\"#1dd3b0\"/>\n</svg>\n",
+        w = width,
+        h = height,
+        m = mid,
+        pts = top
+            .iter()
+            .chain(bottom.iter())
+            .cloned()
+            .collect::<Vec<String>>()
+            .join(" ")
+    )
+}
@@ -0,0 +1,159 @@
+use crate::utils::fs as ufs;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Build cache of a bank (`output/.cache/bank/<publisher>.<name>.toml`).
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct BankCache {
+    /// devaforge version that built the archive
+    version: String,
+    /// Hash of every file of the bank directory, after the build
+    key: String,
+    /// Size of the archive built, to detect a replaced or truncated archive
+    archive_size: u64,
+    /// Content hash of each file, reused while its size and modification time match
+    #[serde(default)]
+    files: BTreeMap<String, CachedFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+struct CachedFile {
+    size: u64,
+    /// Modification time, in nanoseconds since the Unix epoch
+    modified: u64,
+    hash: String,
+}
+
+/// Returns the cache file of a bank.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `publisher`: The bank publisher
+/// - `name`: The bank name
+///
+pub(super) fn cache_path(cwd: &str, publisher: &str, name: &str) -> PathBuf {
+    Path::new(cwd)
+        .join("output")
+        .join(".cache")
+        .join("bank")
+        .join(format!("{}.{}.toml", publisher, name))
+}
+
+/// Returns true if the archive was built by this devaforge version from the current
+/// content of the bank directory (bank.toml, audio, README, LICENSE, ...).
+///
+/// Files whose size and modification time did not change are not hashed again.
+///
+/// ### Parameters
+/// - `cache_file`: The cache file of the bank
+/// - `bank_dir`: The bank directory
+/// - `out_file`: The bank archive
+///
+pub(super) fn is_up_to_date(cache_file: &Path, bank_dir: &Path, out_file: &Path) -> bool {
+    let Some(cache) = read_cache(cache_file) else {
+        return false;
+    };
+    if cache.version != env!("CARGO_PKG_VERSION") {
+        return false;
+    }
+    let archive_size = fs::metadata(out_file).map(|m| m.len()).ok();
+    if archive_size != Some(cache.archive_size) {
+        return false;
+    }
+    match bank_key(bank_dir, &cache.files) {
+        Ok((key, _)) => key == cache.key,
+        Err(_) => false,
+    }
+}
+
+/// Records the state of a bank directory after a successful build.
+///
+/// ### Parameters
+/// - `cache_file`: The cache file of the bank
+/// - `bank_dir`: The bank directory
+/// - `out_file`: The bank archive
+///
+pub(super) fn store(cache_file: &Path, bank_dir: &Path, out_file: &Path) -> Result<(), String> {
+    let previous = read_cache(cache_file).unwrap_or_default();
+    let (key, files) = bank_key(bank_dir, &previous.files)?;
+    let cache = BankCache {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        key,
+        archive_size: fs::metadata(out_file)
+            .map_err(|e| format!("Failed to read {}: {}", out_file.to_string_lossy(), e))?
+            .len(),
+        files,
+    };
+
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+    }
+    let txt = toml::to_string(&cache).map_err(|e| format!("Failed to serialize cache: {}", e))?;
+    fs::write(cache_file, txt).map_err(|e| format!("Failed to write build cache: {}", e))
+}
+
+fn read_cache(cache_file: &Path) -> Option<BankCache> {
+    let txt = fs::read_to_string(cache_file).ok()?;
+    toml::from_str(&txt).ok()
+}
+
+/// Hashes every file of the bank directory, by relative path and content.
+///
+/// Returns the key and the hash of each file.
+fn bank_key(
+    bank_dir: &Path,
+    previous: &BTreeMap<String, CachedFile>,
+) -> Result<(String, BTreeMap<String, CachedFile>), String> {
+    let mut files: BTreeMap<String, CachedFile> = BTreeMap::new();
+    for p in ufs::walk_files(bank_dir)? {
+        let rel = ufs::path_relative_to(&p, bank_dir)
+            .map(ufs::to_unix_string)
+            .unwrap_or_default();
+        let meta = fs::metadata(&p)
+            .map_err(|e| format!("Failed to read {}: {}", p.to_string_lossy(), e))?;
+        let modified = meta
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        let hash = match previous.get(&rel) {
+            Some(f) if f.size == meta.len() && f.modified == modified && modified != 0 => {
+                f.hash.clone()
+            }
+            _ => hash_file(&p)?,
+        };
+        files.insert(
+            rel,
+            CachedFile {
+                size: meta.len(),
+                modified,
+                hash,
+            },
+        );
+    }
+
+    let mut hasher = Sha256::new();
+    for (rel, f) in &files {
+        hasher.update(rel.as_bytes());
+        hasher.update([0]);
+        hasher.update(f.hash.as_bytes());
+        hasher.update([0]);
+    }
+    Ok((hex::encode(hasher.finalize()), files))
+}
+
+fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.to_string_lossy(), e))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    Ok(hex::encode(hasher.finalize()))
+}
@@ -0,0 +1,152 @@
+use super::{DuplicatesSection, TriggerEntry};
+use crate::audio::{decode, fingerprint};
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+/// Similarity from which two samples are reported as near-duplicates.
+const DEFAULT_NEAR_THRESHOLD: f64 = 0.95;
+
+/// Reports samples holding identical audio and, when enabled, near-duplicates.
+///
+/// Identical samples are found by the content hash probed during discovery.
+/// Near-duplicates are compared by audio fingerprint, once per distinct content.
+///
+/// ### Parameters
+/// - `audio_dir`: The source audio directory
+/// - `triggers`: The merged triggers
+/// - `settings`: The `[bank.duplicates]` section
+///
+pub(super) fn report_duplicates(
+    audio_dir: &Path,
+    triggers: &[TriggerEntry],
+    settings: Option<&DuplicatesSection>,
+) -> Result<(), String> {
+    let threshold = settings
+        .and_then(|s| s.threshold)
+        .unwrap_or(DEFAULT_NEAR_THRESHOLD);
+    if !(0.0..=1.0).contains(&threshold) || threshold == 0.0 {
+        return Err(format!(
+            "[bank.duplicates] threshold must be within (0, 1], got {}",
+            threshold
+        ));
+    }
+
+    let by_hash = paths_by_hash(triggers);
+    let logger = Logger::new();
+
+    let exact: Vec<&Vec<String>> = by_hash.values().filter(|p| p.len() > 1).collect();
+    if !exact.is_empty() {
+        let redundant: u64 = exact
+            .iter()
+            .flat_map(|paths| paths.iter().skip(1))
+            .filter_map(|p| fs::metadata(audio_dir.join(p.trim_start_matches("./"))).ok())
+            .map(|m| m.len())
+            .sum();
+        let mut trace: Vec<String> = exact.iter().map(|paths| paths.join(" = ")).collect();
+        trace.sort();
+        let dedupe = settings.and_then(|s| s.dedupe).unwrap_or(false);
+        logger.log_message_with_trace(
+            LogLevel::Warning,
+            &format!(
+                "Found {} groups of identical samples ({} KB redundant){}:",
+                exact.len(),
+                redundant / 1024,
+                if dedupe {
+                    ", packed once"
+                } else {
+                    "; set [bank.duplicates] dedupe = true to pack them once"
+                }
+            ),
+            trace.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+
+    if !settings.and_then(|s| s.near).unwrap_or(false) {
+        return Ok(());
+    }
+
+    // One fingerprint per distinct content, identified by its first path.
+    let mut prints: Vec<(&str, fingerprint::Fingerprint)> = Vec::new();
+    for paths in by_hash.values() {
+        let path = paths[0].as_str();
+        let audio = decode::decode_file(&audio_dir.join(path.trim_start_matches("./")))?;
+        prints.push((path, fingerprint::fingerprint(&audio)));
+    }
+
+    let mut near: Vec<String> = Vec::new();
+    for (i, (path_a, a)) in prints.iter().enumerate() {
+        for (path_b, b) in prints.iter().skip(i + 1) {
+            let score = fingerprint::similarity(a, b);
+            if score >= threshold {
+                let (first, second) = if path_a < path_b {
+                    (path_a, path_b)
+                } else {
+                    (path_b, path_a)
+                };
+                near.push(format!("{} ~ {} ({:.0}%)", first, second, score * 100.0));
+            }
+        }
+    }
+    if !near.is_empty() {
+        near.sort();
+        logger.log_message_with_trace(
+            LogLevel::Warning,
+            &format!("Found {} pairs of near-duplicate samples:", near.len()),
+            near.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+    Ok(())
+}
+
+/// Points every packaged sample at the first copy of its content.
+///
+/// Returns the paths no longer referenced, to leave out of the archive.
+///
+/// ### Parameters
+/// - `triggers`: The packaged triggers
+///
+pub(super) fn dedupe_packaged(triggers: &mut [TriggerEntry]) -> HashSet<String> {
+    let canonical: HashMap<String, String> = paths_by_hash(triggers)
+        .into_iter()
+        .map(|(hash, paths)| (hash, paths[0].clone()))
+        .collect();
+
+    let mut removed: HashSet<String> = HashSet::new();
+    let mut point = |path: &mut String, hash: Option<&String>| {
+        if let Some(first) = hash.and_then(|h| canonical.get(h))
+            && first != path
+        {
+            removed.insert(std::mem::replace(path, first.clone()));
+        }
+    };
+    for t in triggers.iter_mut() {
+        point(&mut t.path, t.props.hash.as_ref());
+        for l in t.layers.iter_mut() {
+            point(&mut l.path, l.props.hash.as_ref());
+        }
+    }
+    removed
+}
+
+/// Groups the distinct sample paths of the triggers by content hash, sorted by path.
+fn paths_by_hash(triggers: &[TriggerEntry]) -> BTreeMap<String, Vec<String>> {
+    let mut by_hash: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+    for t in triggers {
+        let samples = std::iter::once((&t.path, &t.props))
+            .chain(t.layers.iter().map(|l| (&l.path, &l.props)));
+        for (path, props) in samples {
+            if let Some(hash) = &props.hash
+                && seen.insert(path)
+            {
+                by_hash.entry(hash.clone()).or_default().push(path.clone());
+            }
+        }
+    }
+    for paths in by_hash.values_mut() {
+        paths.sort();
+    }
+    by_hash
+}
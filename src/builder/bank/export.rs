@@ -0,0 +1,268 @@
+use super::{TriggerEntry, TriggerLayer, load_bank, loops, stage};
+use crate::audio::probe;
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Export formats accepted by `bank export`.
+const SUPPORTED_EXPORT_FORMATS: &[&str] = &["sfz"];
+
+/// First key of the kit instrument (C2).
+const KIT_FIRST_KEY: u8 = 36;
+
+/// Exports a bank as a self-contained instrument folder.
+///
+/// The folder holds the packaged samples under `samples/` (transcoded and processed
+/// like in the bank archive), one `<trigger>.sfz` per trigger with its full key,
+/// velocity and round-robin mapping, and a `<name>-kit.sfz` kit mapping every trigger to
+/// its own key from C2 upwards. The bank's README.md and LICENSE are copied along.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `id`: The bank identifier `<publisher>.<name>`
+/// - `format`: The export format (`sfz`)
+/// - `out`: The output folder, defaults to `output/sfz/<publisher>.<name>`
+///
+pub fn export_bank(cwd: &str, id: &str, format: &str, out: Option<&str>) -> Result<(), String> {
+    if !SUPPORTED_EXPORT_FORMATS.contains(&format) {
+        return Err(format!(
+            "Unsupported export format '{}' (expected one of: {})",
+            format,
+            SUPPORTED_EXPORT_FORMATS.join(", ")
+        ));
+    }
+
+    let (publisher, name) = id.split_once('.').ok_or_else(|| {
+        format!(
+            "Invalid bank identifier '{}', expected <publisher>.<name>",
+            id
+        )
+    })?;
+    let bank_dir = Path::new(cwd)
+        .join("generated")
+        .join("banks")
+        .join(publisher)
+        .join(name);
+    if !bank_dir.is_dir() {
+        return Err(format!(
+            "Bank '{}' not found under {}",
+            id,
+            bank_dir.parent().unwrap_or(Path::new("")).to_string_lossy()
+        ));
+    }
+    let (_, audio_dir, bank_doc, _) = load_bank(&bank_dir)?;
+
+    let out_dir = match out {
+        Some(o) => {
+            let dir = Path::new(cwd).join(o);
+            if dir.is_dir()
+                && fs::read_dir(&dir)
+                    .map(|mut r| r.next().is_some())
+                    .unwrap_or(false)
+            {
+                return Err(format!(
+                    "Output folder is not empty: {}",
+                    dir.to_string_lossy()
+                ));
+            }
+            dir
+        }
+        None => {
+            let dir = Path::new(cwd)
+                .join("output")
+                .join("sfz")
+                .join(format!("{}.{}", publisher, name));
+            if dir.exists() {
+                fs::remove_dir_all(&dir)
+                    .map_err(|e| format!("Failed to clean {}: {}", dir.to_string_lossy(), e))?;
+            }
+            dir
+        }
+    };
+    fs::create_dir_all(&out_dir)
+        .map_err(|e| format!("Failed to create {}: {}", out_dir.to_string_lossy(), e))?;
+
+    let (triggers, _) = stage::stage_audio(&audio_dir, &out_dir.join("samples"), &bank_doc)?;
+
+    let header = sfz_header(
+        &format!("{}.{}", publisher, name),
+        bank_doc.bank.version.as_deref(),
+        bank_doc.bank.description.as_deref(),
+    );
+    // SFZ choke groups are numbered.
+    let mut choke_ids: BTreeMap<&str, u32> = BTreeMap::new();
+    for group in triggers.iter().filter_map(|t| t.choke_group.as_deref()) {
+        let next = choke_ids.len() as u32 + 1;
+        choke_ids.entry(group).or_insert(next);
+    }
+
+    for t in &triggers {
+        let mut sfz = header.clone();
+        write_trigger_group(&mut sfz, t, None, &choke_ids);
+        write_file(&out_dir.join(format!("{}.sfz", t.name)), &sfz)?;
+    }
+
+    let mut kit = header.clone();
+    let mut skipped: Vec<&str> = Vec::new();
+    for (i, t) in triggers.iter().enumerate() {
+        match u8::try_from(KIT_FIRST_KEY as usize + i) {
+            Ok(key) if key <= 127 => write_trigger_group(&mut kit, t, Some(key), &choke_ids),
+            _ => skipped.push(&t.name),
+        }
+    }
+    write_file(&out_dir.join(format!("{}-kit.sfz", name)), &kit)?;
+
+    for doc in ["README.md", "LICENSE"] {
+        let src = bank_dir.join(doc);
+        if src.is_file() {
+            fs::copy(&src, out_dir.join(doc))
+                .map_err(|e| format!("Failed to copy {}: {}", doc, e))?;
+        }
+    }
+
+    let logger = Logger::new();
+    if !skipped.is_empty() {
+        logger.log_message_with_trace(
+            LogLevel::Warning,
+            "Kit has no key left for these triggers (still exported on their own):",
+            skipped,
+        );
+    }
+    logger.log_message(
+        LogLevel::Success,
+        &format!(
+            "Exported {} triggers to {}",
+            triggers.len(),
+            out_dir.to_string_lossy()
+        ),
+    );
+    Ok(())
+}
+
+fn sfz_header(id: &str, version: Option<&str>, description: Option<&str>) -> String {
+    let mut out = format!("// {}", id);
+    if let Some(v) = version {
+        let _ = write!(out, " v{}", v);
+    }
+    out.push('\n');
+    if let Some(d) = description.filter(|d| !d.trim().is_empty()) {
+        let _ = writeln!(out, "// {}", d.trim());
+    }
+    out.push_str("// Exported by devaforge\n\n<control>\ndefault_path=samples/\n");
+    out
+}
+
+/// Writes a `<group>` holding the layers of a trigger.
+///
+/// With `kit_key`, the trigger is played at its original pitch on that key only, and
+/// key-mapped triggers keep the zone of their default sample. Playback settings become
+/// group opcodes, with choke groups numbered by `choke_ids`.
+fn write_trigger_group(
+    out: &mut String,
+    trigger: &TriggerEntry,
+    kit_key: Option<u8>,
+    choke_ids: &BTreeMap<&str, u32>,
+) {
+    let _ = write!(out, "\n<group>\ngroup_label={}\n", trigger.name);
+
+    let mut settings: Vec<String> = Vec::new();
+    if let Some(id) = trigger
+        .choke_group
+        .as_deref()
+        .and_then(|g| choke_ids.get(g))
+    {
+        settings.push(format!("group={} off_by={}", id, id));
+    }
+    let mode = trigger
+        .mode
+        .as_deref()
+        .or(loops::is_loop(trigger).then_some("loop"));
+    match mode {
+        Some("one_shot") => settings.push("loop_mode=one_shot".into()),
+        Some("gate") => settings.push("loop_mode=no_loop".into()),
+        Some("loop") => settings.push("loop_mode=loop_continuous".into()),
+        _ => {}
+    }
+    if let Some(gain) = trigger.gain {
+        settings.push(format!("volume={}", gain));
+    }
+    if let Some(pan) = trigger.pan {
+        settings.push(format!("pan={}", probe::round_to(pan * 100.0, 1)));
+    }
+    if let Some(voices) = trigger.polyphony {
+        settings.push(format!("polyphony={}", voices));
+    }
+    if !settings.is_empty() {
+        let _ = writeln!(out, "{}", settings.join(" "));
+    }
+
+    let layers = trigger.as_layers();
+    let default_root = layers
+        .iter()
+        .find(|l| l.path == trigger.path)
+        .and_then(|l| l.root);
+    for layer in &layers {
+        if kit_key.is_some()
+            && let (Some(root), Some([lo, hi])) = (default_root, layer.keys)
+            && !(lo..=hi).contains(&root)
+        {
+            continue;
+        }
+
+        let mut opcodes: Vec<String> = Vec::new();
+        match kit_key {
+            Some(key) => opcodes.push(format!("key={} pitch_keycenter={}", key, key)),
+            None => match (layer.root, layer.keys) {
+                (None, None) => opcodes.push("pitch_keytrack=0".into()),
+                (root, keys) => {
+                    let [lo, hi] = keys.unwrap_or([0, 127]);
+                    opcodes.push(format!("lokey={} hikey={}", lo, hi));
+                    opcodes.push(format!("pitch_keycenter={}", root.unwrap_or(60)));
+                }
+            },
+        }
+        if let Some([lo, hi]) = layer.velocity {
+            opcodes.push(format!("lovel={} hivel={}", lo, hi));
+        }
+        if let Some(rr) = layer.round_robin {
+            opcodes.push(format!(
+                "seq_length={} seq_position={}",
+                round_robin_length(&layers, layer),
+                rr
+            ));
+        }
+        if let Some(tune) = layer.tune.filter(|t| *t != 0) {
+            opcodes.push(format!("tune={}", tune));
+        }
+        if let (Some(start), Some(end)) = (layer.loop_start, layer.loop_end)
+            && end > start
+        {
+            // SFZ loop ends are inclusive.
+            opcodes.push(format!(
+                "loop_mode=loop_continuous loop_start={} loop_end={}",
+                start,
+                end - 1
+            ));
+        }
+        // `sample` goes last: its value runs to the end of the line and may hold spaces.
+        opcodes.push(format!("sample={}", layer.path.trim_start_matches("./")));
+        let _ = writeln!(out, "<region> {}", opcodes.join(" "));
+    }
+}
+
+/// Number of round-robin variants sharing the key and velocity range of a layer.
+fn round_robin_length(layers: &[TriggerLayer], layer: &TriggerLayer) -> u32 {
+    layers
+        .iter()
+        .filter(|l| l.keys == layer.keys && l.velocity == layer.velocity)
+        .filter_map(|l| l.round_robin)
+        .max()
+        .unwrap_or(1)
+}
+
+fn write_file(path: &Path, content: &str) -> Result<(), String> {
+    fs::write(path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.to_string_lossy(), e))
+}
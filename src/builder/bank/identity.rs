@@ -0,0 +1,126 @@
+use super::TriggerEntry;
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::{BTreeMap, HashSet};
+
+/// Trigger changes made by a build, compared with the previous bank.toml.
+#[derive(Debug, Default)]
+pub(super) struct TriggerChanges {
+    /// Samples found at a new path: (trigger, old path, new path)
+    pub moved: Vec<(String, String, String)>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+/// Points existing triggers at samples moved or renamed in `audio/`.
+///
+/// A sample missing from its recorded path is matched with a new, unclaimed file
+/// holding the same audio (same content hash), so its trigger keeps its name.
+/// When several files share the content, they are paired by path order.
+///
+/// ### Parameters
+/// - `existing`: The triggers read from bank.toml, rewritten in place
+/// - `discovered`: The triggers discovered in `audio/`
+///
+pub(super) fn follow_moves(
+    existing: &mut [TriggerEntry],
+    discovered: &[TriggerEntry],
+) -> Vec<(String, String, String)> {
+    let known: HashSet<&str> = existing.iter().flat_map(|t| t.sample_paths()).collect();
+    let mut found: HashSet<&str> = HashSet::new();
+    let mut arrived: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for d in discovered {
+        let samples = std::iter::once((&d.path, &d.props))
+            .chain(d.layers.iter().map(|l| (&l.path, &l.props)));
+        for (path, props) in samples {
+            if !found.insert(path) || known.contains(path.as_str()) {
+                continue;
+            }
+            if let Some(hash) = &props.hash {
+                arrived.entry(hash).or_default().push(path);
+            }
+        }
+    }
+
+    let mut gone: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for t in existing.iter() {
+        let samples = std::iter::once((&t.path, &t.props))
+            .chain(t.layers.iter().map(|l| (&l.path, &l.props)));
+        for (path, props) in samples {
+            if let Some(hash) = &props.hash
+                && !found.contains(path.as_str())
+            {
+                let paths = gone.entry(hash.clone()).or_default();
+                if !paths.contains(path) {
+                    paths.push(path.clone());
+                }
+            }
+        }
+    }
+
+    let mut renames: BTreeMap<String, String> = BTreeMap::new();
+    for (hash, mut old_paths) in gone {
+        let Some(new_paths) = arrived.get_mut(hash.as_str()) else {
+            continue;
+        };
+        old_paths.sort();
+        new_paths.sort();
+        for (old, new) in old_paths.into_iter().zip(new_paths.iter()) {
+            renames.insert(old, new.to_string());
+        }
+    }
+
+    let mut moved = Vec::new();
+    for t in existing.iter_mut() {
+        let mut trigger_moved: Vec<(String, String)> = Vec::new();
+        let paths = std::iter::once(&mut t.path).chain(t.layers.iter_mut().map(|l| &mut l.path));
+        for path in paths {
+            if let Some(new) = renames.get(path.as_str()) {
+                let old = std::mem::replace(path, new.clone());
+                if !trigger_moved.iter().any(|(o, _)| *o == old) {
+                    trigger_moved.push((old, new.clone()));
+                }
+            }
+        }
+        moved.extend(
+            trigger_moved
+                .into_iter()
+                .map(|(old, new)| (t.name.clone(), old, new)),
+        );
+    }
+    moved
+}
+
+/// Logs the triggers moved, added and removed by a build.
+///
+/// ### Parameters
+/// - `changes`: The trigger changes
+///
+pub(super) fn report_changes(changes: &TriggerChanges) {
+    let logger = Logger::new();
+    if !changes.moved.is_empty() {
+        let trace: Vec<String> = changes
+            .moved
+            .iter()
+            .map(|(name, old, new)| format!("{}: {} -> {}", name, old, new))
+            .collect();
+        logger.log_message_with_trace(
+            LogLevel::Info,
+            "Samples moved, trigger names kept:",
+            trace.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+    if !changes.added.is_empty() {
+        logger.log_message_with_trace(
+            LogLevel::Info,
+            "Triggers added:",
+            changes.added.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+    if !changes.removed.is_empty() {
+        logger.log_message_with_trace(
+            LogLevel::Warning,
+            "Triggers removed (scripts using them will break):",
+            changes.removed.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use super::{Instrument, Region, parse_key};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Sample attributes translated into bank.toml or without effect on the mapping.
+const HANDLED_ATTRIBUTES: &[&str] = &[
+    "path",
+    "name",
+    "rootNote",
+    "loNote",
+    "hiNote",
+    "loVel",
+    "hiVel",
+    "tuning",
+    "loopStart",
+    "loopEnd",
+    "loopEnabled",
+    "seqPosition",
+    "seqMode",
+    "seqLength",
+    "trigger",
+    "enabled",
+];
+
+/// Reads the regions of a DecentSampler `.dspreset` instrument.
+///
+/// Sample attributes are inherited from their `<group>` and `<groups>` elements.
+///
+/// ### Parameters
+/// - `path`: The `.dspreset` file
+///
+pub(super) fn parse_dspreset(path: &Path) -> Result<Instrument, String> {
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let text = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    let doc = roxmltree::Document::parse(&text)
+        .map_err(|e| format!("Invalid DecentSampler preset: {}", e))?;
+    let root = doc.root_element();
+    if !root.has_tag_name("DecentSampler") {
+        return Err("Invalid DecentSampler preset: missing <DecentSampler> root".into());
+    }
+
+    let mut instrument = Instrument::default();
+    for groups in root.children().filter(|n| n.has_tag_name("groups")) {
+        for group in groups.children().filter(|n| n.has_tag_name("group")) {
+            for sample in group.children().filter(|n| n.has_tag_name("sample")) {
+                let mut attrs: HashMap<&str, &str> = HashMap::new();
+                for node in [groups, group, sample] {
+                    for a in node.attributes() {
+                        attrs.insert(a.name(), a.value());
+                    }
+                }
+                add_region(&attrs, group.attribute("name"), base_dir, &mut instrument);
+            }
+        }
+    }
+    Ok(instrument)
+}
+
+fn add_region(
+    attrs: &HashMap<&str, &str>,
+    group_name: Option<&str>,
+    base_dir: &Path,
+    instrument: &mut Instrument,
+) {
+    for k in attrs.keys() {
+        if !HANDLED_ATTRIBUTES.contains(k) {
+            instrument.ignored.insert(k.to_string());
+        }
+    }
+
+    let Some(sample) = attrs.get("path") else {
+        return;
+    };
+    if attrs.get("enabled") == Some(&"false") {
+        return;
+    }
+    if let Some(trigger) = attrs.get("trigger")
+        && !matches!(*trigger, "attack" | "first" | "legato")
+    {
+        instrument.warnings.push(format!(
+            "Sample with trigger=\"{}\" skipped: {}",
+            trigger, sample
+        ));
+        return;
+    }
+
+    let key = |name: &str| attrs.get(name).and_then(|v| parse_key(v));
+    let number = |name: &str| attrs.get(name).and_then(|v| v.trim().parse::<f64>().ok());
+
+    let mut region = Region {
+        sample: base_dir.join(sample.replace('\\', "/")),
+        group: group_name.map(|s| s.to_string()),
+        ..Default::default()
+    };
+    region.root = key("rootNote").unwrap_or(region.root);
+    region.keys = [
+        key("loNote").unwrap_or(region.keys[0]),
+        key("hiNote").unwrap_or(region.keys[1]),
+    ];
+    if region.keys[0] > region.keys[1] {
+        instrument.warnings.push(format!(
+            "Sample with an empty key range skipped: {}",
+            sample
+        ));
+        return;
+    }
+
+    let lovel = number("loVel").unwrap_or(1.0).clamp(1.0, 127.0) as u8;
+    let hivel = number("hiVel").unwrap_or(127.0).clamp(1.0, 127.0) as u8;
+    region.velocity = [lovel, hivel.max(lovel)];
+
+    // `tuning` is in semitones: whole semitones move the root note, the rest is fine tuning.
+    let tuning = number("tuning").unwrap_or(0.0);
+    let semitones = tuning.trunc() as i32;
+    region.root = (region.root as i32 - semitones).clamp(0, 127) as u8;
+    region.tune = ((tuning - tuning.trunc()) * 100.0).round() as i32;
+
+    region.round_robin = number("seqPosition").map(|n| n.max(1.0) as u32);
+
+    if attrs.get("loopEnabled") != Some(&"false") {
+        region.loop_start = number("loopStart").map(|n| n.max(0.0) as u64);
+        // DecentSampler loop ends are inclusive.
+        region.loop_end = number("loopEnd").map(|n| n.max(0.0) as u64 + 1);
+    }
+
+    instrument.regions.push(region);
+}
@@ -0,0 +1,391 @@
+use super::{SampleProps, TriggerEntry, TriggerLayer, layers, write_triggers_after_bank};
+use crate::addon::bank::scaffold::scaffold_bank;
+use crate::audio::{decode, probe};
+use crate::utils::{
+    fs as ufs,
+    kebab_case::to_kebab_case,
+    logger::{LogLevel, Logger},
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+mod dspreset;
+mod sf2;
+mod sfz;
+
+/// A sample region read from an instrument file.
+#[derive(Debug, Clone)]
+struct Region {
+    /// Sample file, resolved against the instrument file
+    sample: PathBuf,
+    /// Group label, used as trigger name
+    group: Option<String>,
+    root: u8,
+    keys: [u8; 2],
+    velocity: [u8; 2],
+    /// Fine tuning in cents
+    tune: i32,
+    round_robin: Option<u32>,
+    loop_start: Option<u64>,
+    /// Loop end (exclusive), in frames
+    loop_end: Option<u64>,
+}
+
+impl Default for Region {
+    fn default() -> Self {
+        Region {
+            sample: PathBuf::new(),
+            group: None,
+            root: 60,
+            keys: [0, 127],
+            velocity: [1, 127],
+            tune: 0,
+            round_robin: None,
+            loop_start: None,
+            loop_end: None,
+        }
+    }
+}
+
+/// Regions of an instrument file, with the notices raised while reading it.
+#[derive(Debug, Default)]
+struct Instrument {
+    regions: Vec<Region>,
+    /// Settings that have no bank.toml equivalent
+    ignored: BTreeSet<String>,
+    warnings: Vec<String>,
+}
+
+/// Imports an SFZ, DecentSampler or SoundFont 2 instrument as a new bank.
+///
+/// The bank is scaffolded under `generated/banks/<publisher>/<name>/`, the referenced
+/// samples are copied into its `audio/` folder (keeping their layout relative to the
+/// instrument file) and regions become trigger layers in `bank.toml`. Regions sharing
+/// a group label become one trigger; unlabelled regions are grouped under the instrument
+/// file name. SoundFont samples are extracted as WAV and grouped by preset.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `file`: The instrument file (`.sfz`, `.dspreset` or `.sf2`)
+/// - `publisher`: The bank publisher
+/// - `name`: The bank name, defaults to the instrument file name
+/// - `description`: The bank description
+///
+pub async fn import_bank(
+    cwd: &str,
+    file: &str,
+    publisher: String,
+    name: Option<String>,
+    description: Option<String>,
+) -> Result<(), String> {
+    let file_path = Path::new(cwd).join(file);
+    if !file_path.is_file() {
+        return Err(format!(
+            "Instrument file not found: {}",
+            file_path.to_string_lossy()
+        ));
+    }
+    let file_dir = file_path.parent().unwrap_or(Path::new(cwd)).to_path_buf();
+    let stem = file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("imported")
+        .to_string();
+
+    let name = to_kebab_case(name.as_deref().unwrap_or(&stem));
+    let publisher = to_kebab_case(&publisher);
+    if name.is_empty() || publisher.is_empty() {
+        return Err("Bank name and publisher must not be empty".into());
+    }
+    let bank_dir = Path::new(cwd)
+        .join("generated")
+        .join("banks")
+        .join(&publisher)
+        .join(&name);
+    if bank_dir.exists() {
+        return Err(format!(
+            "Bank already exists: {}",
+            bank_dir.to_string_lossy()
+        ));
+    }
+    let description =
+        description.unwrap_or_else(|| format!("Imported from {}", file_name(&file_path)));
+
+    let extension = file_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    let extract_dir = Path::new(cwd)
+        .join("output")
+        .join(".staging")
+        .join("import")
+        .join(format!("{}.{}", publisher, name));
+    let parsed = match extension.as_str() {
+        "sfz" => sfz::parse_sfz(&file_path).map(|i| (i, file_dir)),
+        "dspreset" => dspreset::parse_dspreset(&file_path).map(|i| (i, file_dir)),
+        "sf2" => sf2::parse_sf2(&file_path, &extract_dir).map(|i| (i, extract_dir.clone())),
+        other => Err(format!(
+            "Unsupported instrument format '.{}' (expected .sfz, .dspreset or .sf2)",
+            other
+        )),
+    };
+
+    let result = match parsed {
+        Ok((instrument, base_dir)) => {
+            create_bank(
+                cwd,
+                &publisher,
+                &name,
+                description,
+                &stem,
+                instrument,
+                &base_dir,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    if extract_dir.exists() {
+        let _ = fs::remove_dir_all(&extract_dir);
+    }
+    result
+}
+
+/// Scaffolds the bank, copies the samples of the regions and writes the triggers.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `publisher`: The bank publisher
+/// - `name`: The bank name
+/// - `description`: The bank description
+/// - `stem`: The instrument file name, used for unlabelled regions
+/// - `instrument`: The parsed instrument
+/// - `base_dir`: The folder sample paths are kept relative to
+///
+async fn create_bank(
+    cwd: &str,
+    publisher: &str,
+    name: &str,
+    description: String,
+    stem: &str,
+    mut instrument: Instrument,
+    base_dir: &Path,
+) -> Result<(), String> {
+    // Validate and probe every sample before creating anything.
+    let mut props: HashMap<PathBuf, SampleProps> = HashMap::new();
+    let mut regions: Vec<Region> = Vec::new();
+    for region in instrument.regions.drain(..) {
+        if !props.contains_key(&region.sample) {
+            if !region.sample.is_file() {
+                instrument.warnings.push(format!(
+                    "Sample not found, region skipped: {}",
+                    region.sample.to_string_lossy()
+                ));
+                continue;
+            }
+            if !decode::is_supported(&region.sample) {
+                instrument.warnings.push(format!(
+                    "Unsupported sample format, region skipped: {}",
+                    region.sample.to_string_lossy()
+                ));
+                continue;
+            }
+            let info = probe::probe_file(&region.sample)?;
+            props.insert(region.sample.clone(), SampleProps::from(&info));
+        }
+        regions.push(region);
+    }
+    if regions.is_empty() {
+        return Err("No importable regions found in the instrument".into());
+    }
+
+    scaffold_bank(
+        cwd,
+        name.to_string(),
+        publisher.to_string(),
+        description,
+        "public".to_string(),
+    )
+    .await?;
+    let bank_dir = Path::new(cwd)
+        .join("generated")
+        .join("banks")
+        .join(publisher)
+        .join(name);
+    let audio_dir = bank_dir.join("audio");
+
+    // Copy each sample once, keeping its path relative to the instrument file.
+    let mut copied: HashMap<PathBuf, String> = HashMap::new();
+    let mut used: HashSet<PathBuf> = HashSet::new();
+    let mut by_trigger: BTreeMap<String, Vec<TriggerLayer>> = BTreeMap::new();
+    let mut labels: BTreeMap<String, String> = BTreeMap::new();
+    for region in &regions {
+        let rel = match copied.get(&region.sample) {
+            Some(rel) => rel.clone(),
+            None => {
+                let dest = unique_destination(sample_destination(&region.sample, base_dir), &used);
+                used.insert(dest.clone());
+                let out = audio_dir.join(&dest);
+                if let Some(parent) = out.parent() {
+                    fs::create_dir_all(parent).map_err(|e| {
+                        format!("Failed to create {}: {}", parent.to_string_lossy(), e)
+                    })?;
+                }
+                fs::copy(&region.sample, &out).map_err(|e| {
+                    format!("Failed to copy {}: {}", region.sample.to_string_lossy(), e)
+                })?;
+                let rel = format!("./{}", ufs::to_unix_string(&dest));
+                copied.insert(region.sample.clone(), rel.clone());
+                rel
+            }
+        };
+
+        let label = region
+            .group
+            .as_deref()
+            .filter(|g| !trigger_name(g).is_empty())
+            .unwrap_or(stem)
+            .trim();
+        let trigger = trigger_name(label);
+        labels
+            .entry(trigger.clone())
+            .or_insert_with(|| label.to_string());
+        by_trigger.entry(trigger).or_default().push(TriggerLayer {
+            path: rel,
+            velocity: Some(region.velocity).filter(|v| *v != [1, 127]),
+            round_robin: region.round_robin,
+            root: Some(region.root),
+            keys: Some(region.keys),
+            tune: Some(region.tune).filter(|t| *t != 0),
+            loop_start: region.loop_start,
+            loop_end: region.loop_end,
+            props: props.get(&region.sample).cloned().unwrap_or_default(),
+        });
+    }
+
+    let mut triggers: Vec<TriggerEntry> = by_trigger
+        .into_iter()
+        .map(|(name, layers)| {
+            // The original label is kept when the trigger name had to be simplified.
+            let display_name = labels.get(&name).filter(|l| **l != name).cloned();
+            let mut trigger = TriggerEntry {
+                name,
+                display_name,
+                ..Default::default()
+            };
+            trigger.set_layers(layers);
+            trigger
+        })
+        .collect();
+    triggers.sort_by(|a, b| a.path.cmp(&b.path));
+    write_triggers_after_bank(&bank_dir.join("bank.toml"), &triggers)?;
+
+    let logger = Logger::new();
+    for w in &instrument.warnings {
+        logger.log_message(LogLevel::Warning, w);
+    }
+    if !instrument.ignored.is_empty() {
+        let ignored: Vec<&str> = instrument.ignored.iter().map(|s| s.as_str()).collect();
+        logger.log_message(
+            LogLevel::Warning,
+            &format!(
+                "Settings without a bank equivalent were ignored: {}",
+                ignored.join(", ")
+            ),
+        );
+    }
+    logger.log_message(
+        LogLevel::Success,
+        &format!(
+            "Imported {} regions ({} samples) into {} triggers: {}",
+            regions.len(),
+            copied.len(),
+            triggers.len(),
+            bank_dir.to_string_lossy()
+        ),
+    );
+    Ok(())
+}
+
+/// Parses a MIDI note given as a number (`60`) or a note name (`c4`, `f#3`).
+///
+/// ### Parameters
+/// - `value`: The value to parse
+///
+fn parse_key(value: &str) -> Option<u8> {
+    let value = value.trim();
+    match value.parse::<i32>() {
+        Ok(n) => (0..=127).contains(&n).then_some(n as u8),
+        Err(_) => layers::parse_note(value, false),
+    }
+}
+
+/// Turns a group label or file name into a trigger name (`Sustain Pedal` → `sustain-pedal`).
+///
+/// ### Parameters
+/// - `label`: The label
+///
+fn trigger_name(label: &str) -> String {
+    let mut out = String::new();
+    for c in label.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+        } else if !out.is_empty() && !out.ends_with('-') {
+            out.push('-');
+        }
+    }
+    out.trim_end_matches('-').to_string()
+}
+
+/// Returns the path of a sample inside the bank's `audio/` folder.
+///
+/// Samples outside the instrument's folder are stored by file name.
+///
+/// ### Parameters
+/// - `sample`: The sample file
+/// - `base_dir`: The folder of the instrument file
+///
+fn sample_destination(sample: &Path, base_dir: &Path) -> PathBuf {
+    let rel = sample
+        .strip_prefix(base_dir)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| PathBuf::from(sample.file_name().unwrap_or_default()));
+    rel.components()
+        .filter_map(|c| match c {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect()
+}
+
+fn unique_destination(dest: PathBuf, used: &HashSet<PathBuf>) -> PathBuf {
+    if !used.contains(&dest) {
+        return dest;
+    }
+    let stem = dest
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("sample")
+        .to_string();
+    let ext = dest
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| format!(".{}", e))
+        .unwrap_or_default();
+    let mut i = 2usize;
+    loop {
+        let cand = dest.with_file_name(format!("{}_{}{}", stem, i, ext));
+        if !used.contains(&cand) {
+            return cand;
+        }
+        i += 1;
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
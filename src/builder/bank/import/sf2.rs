@@ -0,0 +1,507 @@
+use super::{Instrument, Region};
+use crate::audio::{decode::DecodedAudio, encode};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Generator operators (SoundFont 2.04, section 8.1.2).
+const GEN_START_ADDRS_OFFSET: u16 = 0;
+const GEN_END_ADDRS_OFFSET: u16 = 1;
+const GEN_STARTLOOP_ADDRS_OFFSET: u16 = 2;
+const GEN_ENDLOOP_ADDRS_OFFSET: u16 = 3;
+const GEN_START_ADDRS_COARSE_OFFSET: u16 = 4;
+const GEN_END_ADDRS_COARSE_OFFSET: u16 = 12;
+const GEN_INSTRUMENT: u16 = 41;
+const GEN_KEY_RANGE: u16 = 43;
+const GEN_VEL_RANGE: u16 = 44;
+const GEN_STARTLOOP_ADDRS_COARSE_OFFSET: u16 = 45;
+const GEN_ENDLOOP_ADDRS_COARSE_OFFSET: u16 = 50;
+const GEN_COARSE_TUNE: u16 = 51;
+const GEN_FINE_TUNE: u16 = 52;
+const GEN_SAMPLE_ID: u16 = 53;
+const GEN_SAMPLE_MODES: u16 = 54;
+const GEN_OVERRIDING_ROOT_KEY: u16 = 58;
+
+/// Generators translated into bank.toml.
+const HANDLED_GENERATORS: &[u16] = &[
+    GEN_STARTLOOP_ADDRS_OFFSET,
+    GEN_ENDLOOP_ADDRS_OFFSET,
+    GEN_INSTRUMENT,
+    GEN_KEY_RANGE,
+    GEN_VEL_RANGE,
+    GEN_STARTLOOP_ADDRS_COARSE_OFFSET,
+    GEN_ENDLOOP_ADDRS_COARSE_OFFSET,
+    GEN_COARSE_TUNE,
+    GEN_FINE_TUNE,
+    GEN_SAMPLE_ID,
+    GEN_SAMPLE_MODES,
+    GEN_OVERRIDING_ROOT_KEY,
+];
+
+/// Sample types (`sfSampleLink`).
+const SAMPLE_RIGHT: u16 = 2;
+const SAMPLE_LEFT: u16 = 4;
+const SAMPLE_ROM: u16 = 0x8000;
+
+/// Coarse address offsets count in blocks of 32768 sample points.
+const COARSE_OFFSET: i64 = 32768;
+
+type Generators = HashMap<u16, [u8; 2]>;
+
+struct PresetHeader {
+    name: String,
+    bag: usize,
+}
+
+struct InstrumentHeader {
+    bag: usize,
+}
+
+struct SampleHeader {
+    name: String,
+    start: u32,
+    end: u32,
+    loop_start: u32,
+    loop_end: u32,
+    sample_rate: u32,
+    original_pitch: u8,
+    pitch_correction: i8,
+    link: u16,
+    kind: u16,
+}
+
+/// Raw chunks of a SoundFont file.
+#[derive(Default)]
+struct Chunks<'a> {
+    smpl: &'a [u8],
+    sm24: Option<&'a [u8]>,
+    phdr: &'a [u8],
+    pbag: &'a [u8],
+    pgen: &'a [u8],
+    inst: &'a [u8],
+    ibag: &'a [u8],
+    igen: &'a [u8],
+    shdr: &'a [u8],
+}
+
+/// Reads the presets of a SoundFont 2 file.
+///
+/// Every sample used by a preset is extracted as WAV into `extract_dir` (stereo pairs
+/// as one stereo file) and every instrument zone becomes a region grouped under its
+/// preset name. Preset-level key/velocity ranges restrict the instrument zones and
+/// preset-level tuning adds to it.
+///
+/// ### Parameters
+/// - `path`: The `.sf2` file
+/// - `extract_dir`: The folder receiving the extracted samples
+///
+pub(super) fn parse_sf2(path: &Path, extract_dir: &Path) -> Result<Instrument, String> {
+    let data =
+        fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"sfbk" {
+        return Err(format!(
+            "Not a SoundFont 2 file: {}",
+            path.to_string_lossy()
+        ));
+    }
+    let chunks = read_chunks(&data[12..])?;
+
+    let presets: Vec<PresetHeader> = records(chunks.phdr, 38)
+        .map(|r| PresetHeader {
+            name: fixed_string(&r[0..20]),
+            bag: u16_at(r, 24) as usize,
+        })
+        .collect();
+    let instruments: Vec<InstrumentHeader> = records(chunks.inst, 22)
+        .map(|r| InstrumentHeader {
+            bag: u16_at(r, 20) as usize,
+        })
+        .collect();
+    let samples: Vec<SampleHeader> = records(chunks.shdr, 46)
+        .map(|r| SampleHeader {
+            name: fixed_string(&r[0..20]),
+            start: u32_at(r, 20),
+            end: u32_at(r, 24),
+            loop_start: u32_at(r, 28),
+            loop_end: u32_at(r, 32),
+            sample_rate: u32_at(r, 36),
+            original_pitch: r[40],
+            pitch_correction: r[41] as i8,
+            link: u16_at(r, 42),
+            kind: u16_at(r, 44),
+        })
+        .collect();
+    let pbag: Vec<usize> = records(chunks.pbag, 4)
+        .map(|r| u16_at(r, 0) as usize)
+        .collect();
+    let ibag: Vec<usize> = records(chunks.ibag, 4)
+        .map(|r| u16_at(r, 0) as usize)
+        .collect();
+    let pgen: Vec<(u16, [u8; 2])> = records(chunks.pgen, 4)
+        .map(|r| (u16_at(r, 0), [r[2], r[3]]))
+        .collect();
+    let igen: Vec<(u16, [u8; 2])> = records(chunks.igen, 4)
+        .map(|r| (u16_at(r, 0), [r[2], r[3]]))
+        .collect();
+    if presets.len() < 2 || instruments.len() < 2 || samples.len() < 2 {
+        return Err("SoundFont has no presets".into());
+    }
+
+    let mut instrument = Instrument::default();
+    let mut extracted: HashMap<usize, PathBuf> = HashMap::new();
+    let mut used_names: HashSet<String> = HashSet::new();
+
+    // The last preset, instrument and sample headers are terminal records.
+    for p in 0..presets.len() - 1 {
+        let preset = &presets[p];
+        let preset_zones = zones(&pbag, &pgen, preset.bag, presets[p + 1].bag, GEN_INSTRUMENT);
+        for pz in &preset_zones {
+            note_ignored(pz, &mut instrument);
+            let Some(inst_idx) = pz
+                .get(&GEN_INSTRUMENT)
+                .map(|a| u16::from_le_bytes(*a) as usize)
+            else {
+                continue;
+            };
+            if inst_idx + 1 >= instruments.len() {
+                continue;
+            }
+            let inst_zones = zones(
+                &ibag,
+                &igen,
+                instruments[inst_idx].bag,
+                instruments[inst_idx + 1].bag,
+                GEN_SAMPLE_ID,
+            );
+            let used_samples: HashSet<usize> = inst_zones
+                .iter()
+                .filter_map(|z| z.get(&GEN_SAMPLE_ID))
+                .map(|a| u16::from_le_bytes(*a) as usize)
+                .collect();
+
+            for iz in &inst_zones {
+                note_ignored(iz, &mut instrument);
+                let Some(sample_idx) = iz
+                    .get(&GEN_SAMPLE_ID)
+                    .map(|a| u16::from_le_bytes(*a) as usize)
+                else {
+                    continue;
+                };
+                let Some(sample) = samples
+                    .get(sample_idx)
+                    .filter(|_| sample_idx + 1 < samples.len())
+                else {
+                    continue;
+                };
+                if sample.kind & SAMPLE_ROM != 0 {
+                    instrument
+                        .warnings
+                        .push(format!("ROM sample '{}' skipped", sample.name));
+                    continue;
+                }
+                // The right channel of a stereo pair is extracted with its left channel.
+                if sample.kind == SAMPLE_RIGHT
+                    && used_samples.contains(&(sample.link as usize))
+                    && stereo_partner(&samples, sample.link as usize).is_some()
+                {
+                    continue;
+                }
+
+                let keys = intersect(range(iz, GEN_KEY_RANGE, 0), range(pz, GEN_KEY_RANGE, 0));
+                let velocity = intersect(range(iz, GEN_VEL_RANGE, 1), range(pz, GEN_VEL_RANGE, 1));
+                let (Some(keys), Some(velocity)) = (keys, velocity) else {
+                    continue;
+                };
+
+                let file = match extracted.get(&sample_idx) {
+                    Some(f) => f.clone(),
+                    None => {
+                        let f = extract_sample(
+                            &chunks,
+                            &samples,
+                            sample_idx,
+                            extract_dir,
+                            &mut used_names,
+                        )?;
+                        extracted.insert(sample_idx, f.clone());
+                        f
+                    }
+                };
+
+                let root = match iz
+                    .get(&GEN_OVERRIDING_ROOT_KEY)
+                    .map(|a| i16::from_le_bytes(*a))
+                {
+                    Some(k) if (0..=127).contains(&k) => k as i32,
+                    _ if sample.original_pitch <= 127 => sample.original_pitch as i32,
+                    _ => 60,
+                };
+                let coarse = amount(iz, GEN_COARSE_TUNE) + amount(pz, GEN_COARSE_TUNE);
+                let fine = amount(iz, GEN_FINE_TUNE)
+                    + amount(pz, GEN_FINE_TUNE)
+                    + sample.pitch_correction as i32;
+
+                let mut region = Region {
+                    sample: file,
+                    group: Some(preset.name.clone()),
+                    root: (root - coarse).clamp(0, 127) as u8,
+                    keys,
+                    velocity: [velocity[0].max(1), velocity[1].max(1)],
+                    tune: fine,
+                    ..Default::default()
+                };
+                // sampleModes 1 and 3 loop; 0 and 2 play through.
+                if matches!(amount(iz, GEN_SAMPLE_MODES), 1 | 3) {
+                    let start = sample.loop_start as i64 - sample.start as i64
+                        + address_offset(
+                            iz,
+                            GEN_STARTLOOP_ADDRS_OFFSET,
+                            GEN_STARTLOOP_ADDRS_COARSE_OFFSET,
+                        );
+                    let end = sample.loop_end as i64 - sample.start as i64
+                        + address_offset(
+                            iz,
+                            GEN_ENDLOOP_ADDRS_OFFSET,
+                            GEN_ENDLOOP_ADDRS_COARSE_OFFSET,
+                        );
+                    if start >= 0 && end > start {
+                        region.loop_start = Some(start as u64);
+                        region.loop_end = Some(end as u64);
+                    }
+                }
+                instrument.regions.push(region);
+            }
+        }
+    }
+    Ok(instrument)
+}
+
+fn read_chunks(mut body: &[u8]) -> Result<Chunks<'_>, String> {
+    let mut chunks = Chunks::default();
+    while body.len() >= 8 {
+        let id = &body[0..4];
+        let size = u32_at(body, 4) as usize;
+        let end = (8 + size).min(body.len());
+        let content = &body[8..end];
+        if id == b"LIST" && content.len() >= 4 {
+            let mut sub = &content[4..];
+            while sub.len() >= 8 {
+                let sub_id = &sub[0..4];
+                let sub_size = u32_at(sub, 4) as usize;
+                let sub_end = (8 + sub_size).min(sub.len());
+                let sub_content = &sub[8..sub_end];
+                match sub_id {
+                    b"smpl" => chunks.smpl = sub_content,
+                    b"sm24" => chunks.sm24 = Some(sub_content),
+                    b"phdr" => chunks.phdr = sub_content,
+                    b"pbag" => chunks.pbag = sub_content,
+                    b"pgen" => chunks.pgen = sub_content,
+                    b"inst" => chunks.inst = sub_content,
+                    b"ibag" => chunks.ibag = sub_content,
+                    b"igen" => chunks.igen = sub_content,
+                    b"shdr" => chunks.shdr = sub_content,
+                    _ => {}
+                }
+                sub = &sub[(sub_end + (sub_size & 1)).min(sub.len())..];
+            }
+        }
+        body = &body[(end + (size & 1)).min(body.len())..];
+    }
+    if chunks.smpl.is_empty() || chunks.shdr.is_empty() || chunks.phdr.is_empty() {
+        return Err("Invalid SoundFont: missing sample data or headers".into());
+    }
+    Ok(chunks)
+}
+
+/// Returns the generators of each zone between two bag indices, with the global zone
+/// (a first zone lacking the `terminal` generator) applied to the other zones.
+fn zones(
+    bags: &[usize],
+    gens: &[(u16, [u8; 2])],
+    first_bag: usize,
+    end_bag: usize,
+    terminal: u16,
+) -> Vec<Generators> {
+    let mut out: Vec<Generators> = Vec::new();
+    let mut global: Option<Generators> = None;
+    for b in first_bag..end_bag {
+        let (Some(start), Some(end)) = (bags.get(b), bags.get(b + 1)) else {
+            break;
+        };
+        let zone: Generators = gens
+            .get(*start..*end)
+            .unwrap_or_default()
+            .iter()
+            .copied()
+            .collect();
+        if b == first_bag && !zone.contains_key(&terminal) {
+            global = Some(zone);
+            continue;
+        }
+        let mut merged = global.clone().unwrap_or_default();
+        merged.extend(zone);
+        out.push(merged);
+    }
+    out
+}
+
+/// Writes a sample (and its right channel when it is the left half of a stereo pair).
+fn extract_sample(
+    chunks: &Chunks,
+    samples: &[SampleHeader],
+    index: usize,
+    extract_dir: &Path,
+    used_names: &mut HashSet<String>,
+) -> Result<PathBuf, String> {
+    let sample = &samples[index];
+    let left = read_points(chunks, sample)?;
+    let right = stereo_partner(samples, index)
+        .map(|r| read_points(chunks, r))
+        .transpose()?;
+
+    let (channels, points) = match right {
+        Some(r) => (
+            2,
+            left.iter()
+                .zip(r.iter())
+                .flat_map(|(l, r)| [*l, *r])
+                .collect::<Vec<f32>>(),
+        ),
+        None => (1, left),
+    };
+    let bit_depth = if chunks.sm24.is_some() { 24 } else { 16 };
+    let audio = DecodedAudio {
+        sample_rate: sample.sample_rate.max(1),
+        channels,
+        bit_depth: Some(bit_depth),
+        samples: points,
+    };
+
+    let mut base = file_stem(&sample.name);
+    if base.is_empty() {
+        base = format!("sample{}", index);
+    }
+    let mut name = base.clone();
+    let mut i = 2usize;
+    while !used_names.insert(name.clone()) {
+        name = format!("{}_{}", base, i);
+        i += 1;
+    }
+
+    fs::create_dir_all(extract_dir)
+        .map_err(|e| format!("Failed to create {}: {}", extract_dir.to_string_lossy(), e))?;
+    let out = extract_dir.join(format!("{}.wav", name));
+    encode::write_wav(&audio, bit_depth, &out)?;
+    Ok(out)
+}
+
+/// Returns the right channel of a left sample of a stereo pair of equal length.
+fn stereo_partner(samples: &[SampleHeader], left: usize) -> Option<&SampleHeader> {
+    let l = samples.get(left).filter(|l| l.kind == SAMPLE_LEFT)?;
+    samples.get(l.link as usize).filter(|r| {
+        r.kind == SAMPLE_RIGHT && r.end.wrapping_sub(r.start) == l.end.wrapping_sub(l.start)
+    })
+}
+
+/// Reads the sample points of a sample as floats.
+fn read_points(chunks: &Chunks, sample: &SampleHeader) -> Result<Vec<f32>, String> {
+    let (start, end) = (sample.start as usize, sample.end as usize);
+    if start > end || end * 2 > chunks.smpl.len() {
+        return Err(format!(
+            "Sample '{}' points outside the sample data",
+            sample.name
+        ));
+    }
+    let lsb = chunks.sm24.filter(|s| s.len() >= end);
+    Ok((start..end)
+        .map(|i| {
+            let hi = i16::from_le_bytes([chunks.smpl[i * 2], chunks.smpl[i * 2 + 1]]);
+            match lsb {
+                Some(l) => ((hi as i32) << 8 | l[i] as i32) as f32 / 8_388_607.0,
+                None => hi as f32 / 32_767.0,
+            }
+        })
+        .collect())
+}
+
+fn note_ignored(zone: &Generators, instrument: &mut Instrument) {
+    for op in zone.keys() {
+        if HANDLED_GENERATORS.contains(op) {
+            continue;
+        }
+        let name = match *op {
+            GEN_START_ADDRS_OFFSET
+            | GEN_END_ADDRS_OFFSET
+            | GEN_START_ADDRS_COARSE_OFFSET
+            | GEN_END_ADDRS_COARSE_OFFSET => "sample start/end offsets".to_string(),
+            8..=11 => "filter".to_string(),
+            15 | 16 => "chorus/reverb send".to_string(),
+            17 => "pan".to_string(),
+            25..=32 => "modulation envelope".to_string(),
+            33..=40 => "volume envelope".to_string(),
+            48 => "attenuation".to_string(),
+            57 => "exclusive class".to_string(),
+            other => format!("generator {}", other),
+        };
+        instrument.ignored.insert(name);
+    }
+}
+
+fn range(zone: &Generators, op: u16, min: u8) -> [u8; 2] {
+    zone.get(&op)
+        .map(|[lo, hi]| [(*lo).max(min), (*hi).min(127)])
+        .unwrap_or([min, 127])
+}
+
+fn intersect(a: [u8; 2], b: [u8; 2]) -> Option<[u8; 2]> {
+    let lo = a[0].max(b[0]);
+    let hi = a[1].min(b[1]);
+    (lo <= hi).then_some([lo, hi])
+}
+
+fn amount(zone: &Generators, op: u16) -> i32 {
+    zone.get(&op)
+        .map(|a| i16::from_le_bytes(*a) as i32)
+        .unwrap_or(0)
+}
+
+fn address_offset(zone: &Generators, fine: u16, coarse: u16) -> i64 {
+    amount(zone, fine) as i64 + amount(zone, coarse) as i64 * COARSE_OFFSET
+}
+
+fn records(data: &[u8], size: usize) -> impl Iterator<Item = &[u8]> {
+    data.chunks_exact(size)
+}
+
+fn fixed_string(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).trim().to_string()
+}
+
+/// Turns a sample name into a file stem.
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>()
+        .trim_matches('_')
+        .to_string()
+}
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
@@ -0,0 +1,307 @@
+use super::{Instrument, Region, parse_key};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Maximum nesting of `#include` directives.
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+/// Opcodes translated into bank.toml or deliberately without effect on the mapping.
+const HANDLED_OPCODES: &[&str] = &[
+    "sample",
+    "default_path",
+    "note_offset",
+    "octave_offset",
+    "key",
+    "lokey",
+    "hikey",
+    "pitch_keycenter",
+    "lovel",
+    "hivel",
+    "tune",
+    "transpose",
+    "loop_mode",
+    "loopmode",
+    "loop_start",
+    "loopstart",
+    "loop_end",
+    "loopend",
+    "seq_position",
+    "seq_length",
+    "trigger",
+    "group_label",
+    "master_label",
+    "region_label",
+    "global_label",
+];
+
+/// Header levels, from the widest to the narrowest scope.
+const LEVELS: [&str; 5] = ["control", "global", "master", "group", "region"];
+
+/// Reads the regions of an SFZ instrument.
+///
+/// Opcodes are inherited from `<global>`, `<master>` and `<group>` headers.
+/// `#define` and `#include` directives are expanded first.
+///
+/// ### Parameters
+/// - `path`: The `.sfz` file
+///
+pub(super) fn parse_sfz(path: &Path) -> Result<Instrument, String> {
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    let mut defines: HashMap<String, String> = HashMap::new();
+    let text = preprocess(path, base_dir, &mut defines, 0)?;
+
+    let mut instrument = Instrument::default();
+    // Opcodes of the current header at each level; a header clears its own level
+    // and every narrower one.
+    let mut scopes: Vec<HashMap<String, String>> = vec![HashMap::new(); LEVELS.len()];
+    let mut level: Option<usize> = None;
+    let mut last_key: Option<String> = None;
+
+    let mut rest = text.as_str();
+    loop {
+        let (segment, header) = match rest.find('<') {
+            Some(open) => {
+                let close = rest[open..]
+                    .find('>')
+                    .map(|c| open + c)
+                    .ok_or_else(|| "Unterminated SFZ header".to_string())?;
+                let header = rest[open + 1..close].trim().to_ascii_lowercase();
+                let segment = &rest[..open];
+                rest = &rest[close + 1..];
+                (segment, Some(header))
+            }
+            None => {
+                let segment = rest;
+                rest = "";
+                (segment, None)
+            }
+        };
+
+        if let Some(l) = level {
+            for token in segment.split_whitespace() {
+                match token.split_once('=') {
+                    Some((k, v)) if !k.is_empty() => {
+                        let k = k.to_ascii_lowercase();
+                        scopes[l].insert(k.clone(), v.to_string());
+                        last_key = Some(k);
+                    }
+                    // Values such as sample paths may contain spaces.
+                    _ => {
+                        if let Some(k) = &last_key
+                            && let Some(v) = scopes[l].get_mut(k)
+                        {
+                            v.push(' ');
+                            v.push_str(token);
+                        }
+                    }
+                }
+            }
+        }
+
+        let region_level = LEVELS.len() - 1;
+        if level == Some(region_level) {
+            emit_region(&scopes, base_dir, &mut instrument);
+        }
+
+        let Some(header) = header else {
+            break;
+        };
+        last_key = None;
+        level = LEVELS.iter().position(|h| *h == header);
+        match level {
+            Some(l) => {
+                for scope in scopes.iter_mut().skip(l) {
+                    scope.clear();
+                }
+            }
+            None => {
+                instrument.ignored.insert(format!("<{}>", header));
+            }
+        }
+    }
+
+    Ok(instrument)
+}
+
+/// Strips comments and expands `#define` and `#include` directives.
+fn preprocess(
+    path: &Path,
+    base_dir: &Path,
+    defines: &mut HashMap<String, String>,
+    depth: usize,
+) -> Result<String, String> {
+    if depth > MAX_INCLUDE_DEPTH {
+        return Err(format!(
+            "Too many nested #include directives at {}",
+            path.to_string_lossy()
+        ));
+    }
+    let raw =
+        fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.to_string_lossy(), e))?;
+    let raw = String::from_utf8_lossy(&raw);
+    let text = strip_block_comments(&raw);
+
+    let mut out = String::new();
+    for line in text.lines() {
+        let line = match line.find("//") {
+            Some(i) => &line[..i],
+            None => line,
+        };
+        let trimmed = line.trim();
+        if let Some(def) = trimmed.strip_prefix("#define") {
+            let mut parts = def.trim().splitn(2, char::is_whitespace);
+            if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+                defines.insert(name.to_string(), value.trim().to_string());
+            }
+            continue;
+        }
+        if let Some(inc) = trimmed.strip_prefix("#include") {
+            let target = inc.trim().trim_matches('"');
+            // Included paths are relative to the main instrument file.
+            let included = base_dir.join(target.replace('\\', "/"));
+            out.push_str(&preprocess(&included, base_dir, defines, depth + 1)?);
+            out.push('\n');
+            continue;
+        }
+        out.push_str(&substitute(line, defines));
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+fn strip_block_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => {
+                rest = "";
+                break;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replaces `$NAME` variables, longest names first so `$NOTE2` wins over `$NOTE`.
+fn substitute(line: &str, defines: &HashMap<String, String>) -> String {
+    if !line.contains('$') {
+        return line.to_string();
+    }
+    let mut names: Vec<&String> = defines.keys().collect();
+    names.sort_by_key(|n| std::cmp::Reverse(n.len()));
+    let mut out = line.to_string();
+    for name in names {
+        out = out.replace(name.as_str(), &defines[name]);
+    }
+    out
+}
+
+/// Merges the opcodes in scope and adds the resulting region.
+fn emit_region(scopes: &[HashMap<String, String>], base_dir: &Path, instrument: &mut Instrument) {
+    let mut opcodes: HashMap<&str, &str> = HashMap::new();
+    for scope in scopes {
+        for (k, v) in scope {
+            opcodes.insert(k.as_str(), v.as_str());
+        }
+    }
+    for k in opcodes.keys() {
+        if !HANDLED_OPCODES.contains(k) {
+            instrument.ignored.insert(k.to_string());
+        }
+    }
+
+    let Some(sample) = opcodes.get("sample").map(|s| s.trim()) else {
+        return;
+    };
+    if sample.starts_with('*') {
+        instrument.warnings.push(format!(
+            "Generated waveform '{}' has no sample, region skipped",
+            sample
+        ));
+        return;
+    }
+    if let Some(trigger) = opcodes.get("trigger")
+        && !matches!(trigger.trim(), "attack" | "first" | "legato")
+    {
+        instrument.warnings.push(format!(
+            "Region with trigger={} skipped: {}",
+            trigger.trim(),
+            sample
+        ));
+        return;
+    }
+
+    let default_path = opcodes.get("default_path").copied().unwrap_or("");
+    let sample_path = format!("{}{}", default_path.trim(), sample).replace('\\', "/");
+
+    let mut region = Region {
+        sample: base_dir.join(sample_path),
+        group: ["group_label", "master_label"]
+            .iter()
+            .find_map(|k| opcodes.get(k))
+            .map(|s| s.trim().to_string()),
+        ..Default::default()
+    };
+
+    let key = |name: &str| opcodes.get(name).and_then(|v| parse_key(v));
+    let number = |name: &str| opcodes.get(name).and_then(|v| v.trim().parse::<f64>().ok());
+
+    if let Some(k) = key("key") {
+        region.keys = [k, k];
+        region.root = k;
+    }
+    if let Some(k) = key("pitch_keycenter") {
+        region.root = k;
+    }
+    region.keys[0] = key("lokey").unwrap_or(region.keys[0]);
+    region.keys[1] = key("hikey").unwrap_or(region.keys[1]);
+
+    let offset = number("note_offset").unwrap_or(0.0) as i32
+        + 12 * number("octave_offset").unwrap_or(0.0) as i32;
+    if offset != 0 {
+        let shift = |n: u8| (n as i32 + offset).clamp(0, 127) as u8;
+        region.keys = [shift(region.keys[0]), shift(region.keys[1])];
+        region.root = shift(region.root);
+    }
+    if region.keys[0] > region.keys[1] {
+        instrument.warnings.push(format!(
+            "Region with an empty key range skipped: {}",
+            sample
+        ));
+        return;
+    }
+
+    let lovel = number("lovel").unwrap_or(1.0).clamp(1.0, 127.0) as u8;
+    let hivel = number("hivel").unwrap_or(127.0).clamp(1.0, 127.0) as u8;
+    region.velocity = [lovel, hivel.max(lovel)];
+
+    // `transpose` shifts the played pitch, which moves the root note the other way.
+    let transpose = number("transpose").unwrap_or(0.0) as i32;
+    region.root = (region.root as i32 - transpose).clamp(0, 127) as u8;
+    region.tune = number("tune").unwrap_or(0.0).round() as i32;
+
+    region.round_robin = number("seq_position")
+        .filter(|_| number("seq_length").is_some_and(|n| n > 1.0))
+        .map(|n| n.max(1.0) as u32);
+
+    let loop_mode = opcodes
+        .get("loop_mode")
+        .or_else(|| opcodes.get("loopmode"))
+        .map(|m| m.trim());
+    if !matches!(loop_mode, Some("no_loop") | Some("one_shot")) {
+        region.loop_start = number("loop_start")
+            .or_else(|| number("loopstart"))
+            .map(|n| n.max(0.0) as u64);
+        // SFZ loop ends are inclusive.
+        region.loop_end = number("loop_end")
+            .or_else(|| number("loopend"))
+            .map(|n| n.max(0.0) as u64 + 1);
+    }
+
+    instrument.regions.push(region);
+}
@@ -0,0 +1,480 @@
+use super::{SampleProps, TriggerEntry, TriggerLayer};
+use crate::audio::chunks::SamplerInfo;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Highest MIDI velocity.
+const MAX_VELOCITY: u32 = 127;
+/// Highest MIDI note.
+const MAX_NOTE: u8 = 127;
+/// Middle C, preferred as the default sample of key-mapped triggers.
+const MIDDLE_C: u8 = 60;
+
+/// Layer information carried by a sample file name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct StemTags {
+    /// File stem without its layer suffixes
+    pub base: String,
+    pub velocity_index: Option<u32>,
+    pub round_robin: Option<u32>,
+    /// Root MIDI note
+    pub root: Option<u8>,
+}
+
+/// A discovered sample, before grouping into triggers.
+pub(super) struct DiscoveredSample {
+    /// Relative path with a `./` prefix
+    pub path: String,
+    /// File stem the tags were parsed from
+    pub stem: String,
+    /// Parent directory relative to the audio directory
+    pub dir: String,
+    pub tags: StemTags,
+    pub props: SampleProps,
+    /// Sampler metadata embedded in the file
+    pub sampler: Option<SamplerInfo>,
+}
+
+impl DiscoveredSample {
+    /// Root note from the file name, falling back to the embedded metadata.
+    fn root(&self) -> Option<u8> {
+        self.tags
+            .root
+            .or_else(|| self.sampler.as_ref().and_then(|i| i.root))
+    }
+}
+
+/// Splits a sample stem into its base name and layer suffixes.
+///
+/// Suffixes are trailing `_`/`-` separated tokens in any order. With `layer_suffixes`,
+/// `v<N>` or `vel<N>` mark velocity layers and `rr<N>` round-robin variants, e.g.
+/// `snare_v1_rr2` → (`snare`, v1, rr2). With `note_suffixes`, a note name with an
+/// uppercase letter (`C4`, `F#3`, `Bb2`, with C4 = 60) or a three-digit MIDI note
+/// (`036`) sets the root key, e.g. `piano_C4` → (`piano`, 60). Both are opt-in since
+/// version, take and variant names such as `vox_v2`, `kick_a1` or `kick_001` are
+/// common.
+///
+/// ### Parameters
+/// - `stem`: The file stem
+/// - `layer_suffixes`: Whether `v<N>`, `vel<N>` and `rr<N>` are layer suffixes
+/// - `note_suffixes`: Whether note names and three-digit numbers are root notes
+///
+pub(super) fn parse_layer_stem(stem: &str, layer_suffixes: bool, note_suffixes: bool) -> StemTags {
+    let mut tags = StemTags::default();
+    let mut base = stem;
+
+    while let Some(idx) = base.rfind(['_', '-']) {
+        let raw = &base[idx + 1..];
+        let token = raw.to_ascii_lowercase();
+        if layer_suffixes
+            && tags.round_robin.is_none()
+            && let Some(n) = token.strip_prefix("rr").and_then(|n| n.parse().ok())
+        {
+            tags.round_robin = Some(n);
+        } else if layer_suffixes
+            && tags.velocity_index.is_none()
+            && let Some(n) = token
+                .strip_prefix("vel")
+                .or_else(|| token.strip_prefix('v'))
+                .and_then(|n| n.parse().ok())
+        {
+            tags.velocity_index = Some(n);
+        } else if note_suffixes
+            && tags.root.is_none()
+            && let Some(note) = parse_root_suffix(raw)
+        {
+            tags.root = Some(note);
+        } else if note_suffixes
+            && tags.root.is_none()
+            && base[idx..].starts_with('-')
+            && let Some(prev) = base[..idx].rfind(['_', '-'])
+            && let Some(note) = parse_root_suffix(&base[prev + 1..])
+        {
+            // Negative octave, e.g. `C-1`: the note spans the dash.
+            tags.root = Some(note);
+            base = &base[..prev];
+            continue;
+        } else {
+            break;
+        }
+        base = &base[..idx];
+    }
+
+    if base.is_empty() {
+        return StemTags {
+            base: stem.to_string(),
+            ..Default::default()
+        };
+    }
+    tags.base = base.to_string();
+    tags
+}
+
+/// Parses a root note suffix: a note name starting with an uppercase letter (`C4`,
+/// `Bb2`, `C-1`) or a three-digit MIDI note number (`036`).
+fn parse_root_suffix(token: &str) -> Option<u8> {
+    let first = token.chars().next()?;
+    if first.is_ascii_lowercase() {
+        return None;
+    }
+    parse_note(token, true)
+}
+
+/// Parses a note name (`C4`, `f#3`, `Bb2`) or a three-digit MIDI note number (`036`).
+///
+/// ### Parameters
+/// - `token`: The token to parse
+/// - `numeric`: Whether three-digit MIDI note numbers are accepted
+///
+pub(super) fn parse_note(token: &str, numeric: bool) -> Option<u8> {
+    if token.len() == 3 && token.chars().all(|c| c.is_ascii_digit()) {
+        if !numeric {
+            return None;
+        }
+        return token.parse::<u8>().ok().filter(|n| *n <= MAX_NOTE);
+    }
+
+    let mut chars = token.chars();
+    let semitone: i32 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let rest: String = chars.collect();
+    let (accidental, octave) = if let Some(o) = rest.strip_prefix('#') {
+        (1, o)
+    } else if let Some(o) = rest.strip_prefix('b') {
+        (-1, o)
+    } else {
+        (0, rest.as_str())
+    };
+    if octave.is_empty() || octave.len() > 2 || octave == "-" {
+        return None;
+    }
+    let octave: i32 = octave.parse().ok()?;
+    let note = (octave + 1) * 12 + semitone + accidental;
+    (0..=MAX_NOTE as i32).contains(&note).then_some(note as u8)
+}
+
+/// Splits the MIDI velocity range evenly between velocity layer indices.
+///
+/// The lowest index gets the softest range.
+///
+/// ### Parameters
+/// - `indices`: The distinct velocity layer indices
+///
+pub(super) fn velocity_ranges(indices: &[u32]) -> BTreeMap<u32, [u8; 2]> {
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let n = sorted.len() as u32;
+    sorted
+        .into_iter()
+        .enumerate()
+        .map(|(i, idx)| {
+            let i = i as u32;
+            let low = MAX_VELOCITY * i / n + 1;
+            let high = MAX_VELOCITY * (i + 1) / n;
+            (idx, [low as u8, high as u8])
+        })
+        .collect()
+}
+
+/// Splits the keyboard between root notes, each zone reaching halfway to its
+/// neighbours. The lowest and highest zones extend to the ends of the keyboard.
+///
+/// ### Parameters
+/// - `roots`: The distinct root notes
+///
+pub(super) fn key_ranges(roots: &[u8]) -> BTreeMap<u8, [u8; 2]> {
+    let mut sorted = roots.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+    let mut out = BTreeMap::new();
+    for (i, root) in sorted.iter().enumerate() {
+        let low = if i == 0 {
+            0
+        } else {
+            (sorted[i - 1] as u16 + *root as u16) / 2 + 1
+        };
+        let high = match sorted.get(i + 1) {
+            Some(next) => (*root as u16 + *next as u16) / 2,
+            None => MAX_NOTE as u16,
+        };
+        out.insert(*root, [low as u8, high as u8]);
+    }
+    out
+}
+
+/// Groups discovered samples into triggers using their naming convention.
+///
+/// Samples sharing a directory and base name become the layers of one trigger. A
+/// sample without layer suffixes, embedded sampler metadata or siblings stays a
+/// plain one-sample trigger.
+///
+/// Name suffixes take precedence over embedded metadata for the root note, and
+/// ranges derived from the naming convention over embedded key/velocity ranges.
+/// Loop points and fine tuning only come from embedded metadata.
+///
+/// Suffixes only count when they relate several files: velocity and round-robin
+/// suffixes need at least two files sharing the base name, and root notes at least
+/// two distinct roots. A lone `lead_v2.wav` or `tom_E1.wav` keeps its full name.
+///
+/// ### Parameters
+/// - `samples`: The discovered samples
+///
+pub(super) fn group_samples(mut samples: Vec<DiscoveredSample>) -> Vec<TriggerEntry> {
+    let mut files: BTreeMap<(String, String), usize> = BTreeMap::new();
+    let mut roots: BTreeMap<(String, String), BTreeSet<u8>> = BTreeMap::new();
+    for s in &samples {
+        let key = (s.dir.clone(), s.tags.base.clone());
+        if let Some(root) = s.tags.root {
+            roots.entry(key.clone()).or_default().insert(root);
+        }
+        *files.entry(key).or_default() += 1;
+    }
+    for s in samples.iter_mut() {
+        let key = (s.dir.clone(), s.tags.base.clone());
+        let has_layer = s.tags.velocity_index.is_some() || s.tags.round_robin.is_some();
+        let keep_layers = has_layer && files.get(&key).is_some_and(|n| *n >= 2);
+        let keep_root = s.tags.root.is_some() && roots.get(&key).is_some_and(|r| r.len() >= 2);
+        if keep_layers != has_layer || keep_root != s.tags.root.is_some() {
+            s.tags = parse_layer_stem(&s.stem, keep_layers, keep_root);
+        }
+    }
+
+    let mut groups: BTreeMap<(String, String), Vec<DiscoveredSample>> = BTreeMap::new();
+    for s in samples {
+        groups
+            .entry((s.dir.clone(), s.tags.base.clone()))
+            .or_default()
+            .push(s);
+    }
+
+    let mut out: Vec<TriggerEntry> = Vec::new();
+    for ((_, base), mut group) in groups {
+        if group.len() == 1
+            && group[0].tags == plain_tags(&group[0].tags.base)
+            && group[0].sampler.is_none()
+        {
+            let s = group.remove(0);
+            out.push(TriggerEntry {
+                name: s.tags.base,
+                path: s.path,
+                props: s.props,
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let layered = group.iter().any(|s| s.tags.velocity_index.is_some());
+        // Unnumbered samples in a layered group count as the first layer.
+        let velocity_index = |s: &DiscoveredSample| s.tags.velocity_index.unwrap_or(1);
+        let indices: Vec<u32> = group.iter().map(velocity_index).collect();
+        let velocities = velocity_ranges(&indices);
+
+        // Key zones are split independently for each velocity layer / round-robin slot.
+        let mut roots_by_slot: BTreeMap<(u32, Option<u32>), Vec<u8>> = BTreeMap::new();
+        for s in &group {
+            if let Some(root) = s.root() {
+                roots_by_slot
+                    .entry((velocity_index(s), s.tags.round_robin))
+                    .or_default()
+                    .push(root);
+            }
+        }
+        let keys_by_slot: BTreeMap<(u32, Option<u32>), BTreeMap<u8, [u8; 2]>> = roots_by_slot
+            .into_iter()
+            .map(|(slot, roots)| (slot, key_ranges(&roots)))
+            .collect();
+
+        let layers: Vec<TriggerLayer> = group
+            .into_iter()
+            .map(|s| {
+                let slot = (velocity_index(&s), s.tags.round_robin);
+                let root = s.root();
+                let sampler = s.sampler.unwrap_or_default();
+                let derived_keys = s
+                    .tags
+                    .root
+                    .or(root.filter(|_| sampler.keys.is_none()))
+                    .and_then(|r| keys_by_slot.get(&slot).and_then(|k| k.get(&r)).copied());
+                TriggerLayer {
+                    path: s.path,
+                    velocity: if layered {
+                        velocities.get(&slot.0).copied()
+                    } else {
+                        sampler.velocity
+                    },
+                    round_robin: s.tags.round_robin,
+                    root,
+                    keys: derived_keys.or(sampler.keys),
+                    tune: sampler.tune,
+                    loop_start: sampler.loop_start,
+                    loop_end: sampler.loop_end,
+                    props: s.props,
+                }
+            })
+            .collect();
+
+        let mut trigger = TriggerEntry {
+            name: base,
+            ..Default::default()
+        };
+        trigger.set_layers(layers);
+        out.push(trigger);
+    }
+    out
+}
+
+fn plain_tags(base: &str) -> StemTags {
+    StemTags {
+        base: base.to_string(),
+        ..Default::default()
+    }
+}
+
+/// Orders layers by key range, velocity range, round-robin position, then path.
+///
+/// ### Parameters
+/// - `layers`: The layers to sort
+///
+pub(super) fn sort_layers(layers: &mut [TriggerLayer]) {
+    layers.sort_by(|a, b| {
+        a.keys
+            .cmp(&b.keys)
+            .then(a.velocity.cmp(&b.velocity))
+            .then(a.round_robin.cmp(&b.round_robin))
+            .then(a.path.cmp(&b.path))
+    });
+}
+
+/// Picks the layer used as the trigger's default sample: the zone closest to
+/// middle C, loudest velocity layer, first round-robin variant.
+///
+/// ### Parameters
+/// - `layers`: The layers of a trigger
+///
+pub(super) fn primary_layer(layers: &[TriggerLayer]) -> Option<&TriggerLayer> {
+    layers.iter().min_by(|a, b| {
+        let ka = a.root.map(|r| r.abs_diff(MIDDLE_C)).unwrap_or(0);
+        let kb = b.root.map(|r| r.abs_diff(MIDDLE_C)).unwrap_or(0);
+        let va = a.velocity.map(|v| v[1]).unwrap_or(0);
+        let vb = b.velocity.map(|v| v[1]).unwrap_or(0);
+        ka.cmp(&kb)
+            .then(vb.cmp(&va))
+            .then(a.round_robin.unwrap_or(0).cmp(&b.round_robin.unwrap_or(0)))
+            .then(a.path.cmp(&b.path))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(stem: &str, note_suffixes: bool) -> DiscoveredSample {
+        DiscoveredSample {
+            path: format!("./{}.wav", stem),
+            stem: stem.to_string(),
+            dir: String::new(),
+            tags: parse_layer_stem(stem, true, note_suffixes),
+            props: SampleProps::default(),
+            sampler: None,
+        }
+    }
+
+    fn names(stems: &[&str], note_suffixes: bool) -> Vec<String> {
+        let samples = stems.iter().map(|s| sample(s, note_suffixes)).collect();
+        group_samples(samples).into_iter().map(|t| t.name).collect()
+    }
+
+    #[test]
+    fn note_suffixes_are_opt_in() {
+        assert_eq!(parse_layer_stem("piano_C4", true, false).root, None);
+        assert_eq!(parse_layer_stem("piano_C4", true, false).base, "piano_C4");
+        assert_eq!(parse_layer_stem("bass_036", true, false).root, None);
+        assert_eq!(parse_layer_stem("piano_C4", true, true).root, Some(60));
+        assert_eq!(parse_layer_stem("bass_036", true, true).root, Some(36));
+        assert_eq!(parse_layer_stem("pad_C-1", true, true).root, Some(0));
+    }
+
+    #[test]
+    fn lowercase_take_names_are_not_notes() {
+        for stem in ["kick_a1", "kick_a2", "tom_e1", "fx_b2", "hat_c1"] {
+            let tags = parse_layer_stem(stem, true, true);
+            assert_eq!(tags.root, None, "{}", stem);
+            assert_eq!(tags.base, stem);
+        }
+        assert_eq!(
+            names(&["kick_a1", "kick_a2", "tom_e1", "fx_b2", "hat_c1"], true),
+            ["fx_b2", "hat_c1", "kick_a1", "kick_a2", "tom_e1"]
+        );
+    }
+
+    #[test]
+    fn lone_root_keeps_its_name() {
+        assert_eq!(names(&["tom_E1"], true), ["tom_E1"]);
+        assert_eq!(names(&["piano", "piano_C4"], true), ["piano", "piano_C4"]);
+
+        let triggers = group_samples(vec![sample("hat_C1_rr1", true), sample("hat_C1_rr2", true)]);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].name, "hat_C1");
+        assert!(triggers[0].layers.iter().all(|l| l.root.is_none()));
+    }
+
+    #[test]
+    fn distinct_roots_form_a_multisample() {
+        let triggers = group_samples(vec![sample("piano_C3", true), sample("piano_C4", true)]);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].name, "piano");
+        let roots: Vec<Option<u8>> = triggers[0].layers.iter().map(|l| l.root).collect();
+        assert_eq!(roots, [Some(48), Some(60)]);
+    }
+
+    #[test]
+    fn layer_suffixes_are_opt_in() {
+        let tags = parse_layer_stem("vox_v2", false, false);
+        assert_eq!(tags.velocity_index, None);
+        assert_eq!(tags.base, "vox_v2");
+
+        let samples = ["vox_v1", "vox_v2"]
+            .iter()
+            .map(|stem| DiscoveredSample {
+                tags: parse_layer_stem(stem, false, false),
+                ..sample(stem, false)
+            })
+            .collect();
+        let names: Vec<String> = group_samples(samples).into_iter().map(|t| t.name).collect();
+        assert_eq!(names, ["vox_v1", "vox_v2"]);
+    }
+
+    #[test]
+    fn lone_layer_keeps_its_name() {
+        for stem in ["lead_v2", "lead_vel3", "lead_rr1", "lead_v2_rr1"] {
+            let triggers = group_samples(vec![sample(stem, false)]);
+            assert_eq!(triggers.len(), 1);
+            assert_eq!(triggers[0].name, stem);
+            assert!(triggers[0].layers.is_empty(), "{}", stem);
+        }
+        assert_eq!(
+            names(&["lead_v2", "pad_rr1"], false),
+            ["lead_v2", "pad_rr1"]
+        );
+    }
+
+    #[test]
+    fn shared_base_forms_layers() {
+        let triggers = group_samples(vec![
+            sample("snare_v1_rr1", false),
+            sample("snare_v1_rr2", false),
+            sample("snare_v2_rr1", false),
+        ]);
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].name, "snare");
+        assert_eq!(triggers[0].layers.len(), 3);
+    }
+}
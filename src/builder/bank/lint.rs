@@ -0,0 +1,229 @@
+use super::{BankToml, SampleProps, load_bank, playback, resolve_bank_dir};
+use crate::audio::{decode, probe};
+use crate::builder::lint::{self, AddonReport, Finding, LintFormat};
+use crate::utils::fs as ufs;
+use std::fs;
+use std::path::Path;
+
+/// Longest sample file name accepted without a warning, extension included.
+const MAX_FILE_NAME_LEN: usize = 64;
+
+/// Checks banks without building them and prints the problems found.
+///
+/// Fails when any bank has an error, so it can gate merges; warnings alone pass.
+///
+/// ### Parameters
+/// - `path`: The bank to lint, or None to lint every bank under `generated/banks`
+/// - `cwd`: The current working directory
+/// - `format`: The output format: `human` or `json`
+///
+pub fn lint_banks(path: Option<&str>, cwd: &str, format: &str) -> Result<(), String> {
+    let format = LintFormat::parse(format)?;
+    let banks_root = Path::new(cwd).join("generated").join("banks");
+    let only = match path {
+        Some(p) => Some(resolve_bank_dir(cwd, p)?),
+        None => None,
+    };
+    let reports: Vec<AddonReport> = lint::addon_dirs(&banks_root, "bank.toml", only)?
+        .iter()
+        .map(|dir| lint_bank(dir, &banks_root))
+        .collect();
+    lint::print_reports(&reports, format)
+}
+
+/// Runs every check on one bank.
+///
+/// ### Parameters
+/// - `bank_dir`: The bank directory
+/// - `banks_root`: The `generated/banks` directory, to shorten the reported path
+///
+fn lint_bank(bank_dir: &Path, banks_root: &Path) -> AddonReport {
+    let mut report = AddonReport {
+        kind: "bank",
+        id: ufs::path_relative_to(bank_dir, banks_root)
+            .map(ufs::to_unix_string)
+            .unwrap_or_else(|| bank_dir.to_string_lossy().to_string()),
+        path: bank_dir.to_string_lossy().to_string(),
+        findings: Vec::new(),
+    };
+
+    let doc: BankToml = match fs::read_to_string(bank_dir.join("bank.toml"))
+        .map_err(|e| e.to_string())
+        .and_then(|txt| toml::from_str(&txt).map_err(|e| e.to_string()))
+    {
+        Ok(doc) => doc,
+        Err(e) => {
+            report.findings.push(Finding::error(
+                "invalid-manifest",
+                Some("bank.toml".to_string()),
+                format!("Cannot read bank.toml: {}", e),
+            ));
+            return report;
+        }
+    };
+    let bank = &doc.bank;
+    if !bank.publisher.is_empty() && !bank.name.is_empty() {
+        report.id = format!("{}.{}", bank.publisher, bank.name);
+    }
+    report.findings.extend(lint::check_manifest(
+        "bank",
+        &bank.publisher,
+        &bank.name,
+        bank.version.as_deref(),
+        bank.access.as_deref(),
+    ));
+    report.findings.extend(lint::check_docs(bank_dir));
+
+    let audio_dir = bank_dir.join("audio");
+    if !audio_dir.is_dir() {
+        report.findings.push(Finding::error(
+            "missing-audio",
+            None,
+            "audio/ folder is missing".to_string(),
+        ));
+        return report;
+    }
+    report.findings.extend(check_audio_files(&audio_dir));
+
+    // Samples are decoded the same way as a build, which also merges the triggers.
+    match load_bank(bank_dir) {
+        Ok((_, _, merged, _)) => {
+            for trigger in &merged.triggers {
+                if !is_trigger_identifier(&trigger.name) {
+                    report.findings.push(Finding::error(
+                        "invalid-trigger-name",
+                        Some(audio_file(&trigger.path)),
+                        format!(
+                            "Trigger name '{}' is not a valid Devalang identifier (letters, digits, '_' and '-', segments separated by '.'); set `name` on its [[triggers]] entry or rename the file",
+                            trigger.name
+                        ),
+                    ));
+                }
+                if trigger.layers.is_empty() {
+                    report
+                        .findings
+                        .extend(check_sample(&trigger.path, &trigger.props));
+                }
+                for layer in &trigger.layers {
+                    report
+                        .findings
+                        .extend(check_sample(&layer.path, &layer.props));
+                }
+            }
+            for (group, name) in playback::lonely_choke_groups(&merged.triggers) {
+                report.findings.push(Finding::warning(
+                    "lonely-choke-group",
+                    Some("bank.toml".to_string()),
+                    format!(
+                        "Choke group '{}' only holds trigger '{}' and has no effect",
+                        group, name
+                    ),
+                ));
+            }
+        }
+        Err(e) => report.findings.push(Finding::error(
+            "invalid-bank",
+            None,
+            format!("Bank cannot be built: {}", e),
+        )),
+    }
+    report
+}
+
+/// Reports files in `audio/` a build would leave out, and overly long file names.
+///
+/// ### Parameters
+/// - `audio_dir`: The bank's audio directory
+///
+fn check_audio_files(audio_dir: &Path) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut files = match ufs::walk_files(audio_dir) {
+        Ok(files) => files,
+        Err(e) => {
+            findings.push(Finding::error("unreadable-audio", None, e));
+            return findings;
+        }
+    };
+    files.sort();
+    for p in files {
+        let rel = ufs::path_relative_to(&p, audio_dir)
+            .map(ufs::to_unix_string)
+            .unwrap_or_default();
+        let file = Some(format!("audio/{}", rel));
+        if !decode::is_supported(&p) {
+            findings.push(Finding::warning(
+                "unsupported-file",
+                file,
+                format!(
+                    "Not a supported audio file ({}); it is left out of the bank",
+                    decode::SUPPORTED_EXTENSIONS.join(", ")
+                ),
+            ));
+            continue;
+        }
+        let name_len = p
+            .file_name()
+            .map(|n| n.to_string_lossy().chars().count())
+            .unwrap_or(0);
+        if name_len > MAX_FILE_NAME_LEN {
+            findings.push(Finding::warning(
+                "long-file-name",
+                file,
+                format!(
+                    "File name is {} characters long (max {})",
+                    name_len, MAX_FILE_NAME_LEN
+                ),
+            ));
+        }
+    }
+    findings
+}
+
+/// Reports empty, silent and clipped samples.
+///
+/// ### Parameters
+/// - `path`: The sample path, relative to `audio/`
+/// - `props`: The properties measured on the sample
+///
+fn check_sample(path: &str, props: &SampleProps) -> Vec<Finding> {
+    let file = Some(audio_file(path));
+    let mut findings = Vec::new();
+    if props.duration.is_some_and(|d| d <= 0.0) {
+        findings.push(Finding::error(
+            "empty-sample",
+            file,
+            "Sample holds no audio".to_string(),
+        ));
+    } else if props.peak_db.is_some_and(|p| p <= probe::SILENCE_FLOOR_DB) {
+        findings.push(Finding::warning(
+            "silent-sample",
+            file,
+            "Sample is digital silence".to_string(),
+        ));
+    } else if props.peak_db.is_some_and(|p| p >= 0.0) {
+        findings.push(Finding::warning(
+            "clipped-sample",
+            file,
+            "Sample peaks at 0 dBFS and is likely clipped".to_string(),
+        ));
+    }
+    findings
+}
+
+/// Returns the path of a sample relative to the bank directory (`./kick.wav` → `audio/kick.wav`).
+fn audio_file(path: &str) -> String {
+    format!("audio/{}", path.trim_start_matches("./"))
+}
+
+/// Returns true if a trigger name can be referenced from Devalang: non-empty segments
+/// of ASCII letters, digits, `_` and `-`, separated by `.` (`kick`, `snares.snare-01`).
+fn is_trigger_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name.split('.').all(|segment| {
+            !segment.is_empty()
+                && !segment.starts_with('-')
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        })
+}
@@ -0,0 +1,85 @@
+use super::TriggerEntry;
+use crate::audio::{decode, key, probe, tempo};
+use crate::utils::logger::{LogLevel, Logger};
+use std::path::Path;
+
+/// Largest distance to a whole number of beats, in beats, for a loop to fit its tempo.
+const BEAT_TOLERANCE: f64 = 0.02;
+
+/// Returns true if the trigger is a loop: tagged `loop`, in a `loop`/`loops`
+/// category, or with a declared tempo.
+///
+/// ### Parameters
+/// - `trigger`: The trigger
+///
+pub(super) fn is_loop(trigger: &TriggerEntry) -> bool {
+    trigger.bpm.is_some()
+        || trigger.tags.iter().any(|t| t.eq_ignore_ascii_case("loop"))
+        || trigger
+            .category
+            .as_deref()
+            .is_some_and(|c| c.eq_ignore_ascii_case("loop") || c.eq_ignore_ascii_case("loops"))
+}
+
+/// Estimates the tempo and key of every loop trigger and warns about loops that do
+/// not last a whole number of beats.
+///
+/// Estimates are refreshed on every build in `detected_bpm` / `detected_key`; the
+/// `bpm` / `key` fields are user overrides and are never written here.
+///
+/// ### Parameters
+/// - `audio_dir`: The source audio directory
+/// - `triggers`: The merged triggers
+///
+pub(super) fn analyze_loops(audio_dir: &Path, triggers: &mut [TriggerEntry]) -> Result<(), String> {
+    let mut off_grid: Vec<String> = Vec::new();
+    for t in triggers.iter_mut() {
+        if !is_loop(t) {
+            t.detected_bpm = None;
+            t.detected_key = None;
+            continue;
+        }
+
+        let audio = decode::decode_file(&audio_dir.join(t.path.trim_start_matches("./")))?;
+        t.detected_bpm = tempo::estimate_bpm(&audio);
+        t.detected_key = key::estimate_key(&audio);
+
+        let Some(bpm) = t.bpm.or(t.detected_bpm) else {
+            continue;
+        };
+        let beats = tempo::beats(audio.duration_secs(), bpm);
+        if (beats - beats.round()).abs() > BEAT_TOLERANCE {
+            off_grid.push(format!(
+                "{}: {} beats at {} BPM",
+                t.name,
+                probe::round_to(beats, 2),
+                bpm
+            ));
+        }
+    }
+
+    if !off_grid.is_empty() {
+        Logger::new().log_message_with_trace(
+            LogLevel::Warning,
+            "Loops not lasting a whole number of beats:",
+            off_grid.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+    Ok(())
+}
+
+/// Resolves the tempo and key of packaged loops: overrides win over estimates.
+/// Loops without a playback mode are played in `loop` mode.
+///
+/// ### Parameters
+/// - `triggers`: The packaged triggers
+///
+pub(super) fn resolve_packaged(triggers: &mut [TriggerEntry]) {
+    for t in triggers.iter_mut() {
+        if t.mode.is_none() && is_loop(t) {
+            t.mode = Some("loop".into());
+        }
+        t.bpm = t.bpm.or(t.detected_bpm.take());
+        t.key = t.key.take().or(t.detected_key.take());
+    }
+}
@@ -0,0 +1,1209 @@
+use crate::audio::{chunks, decode, probe};
+use crate::builder::watch;
+use crate::utils::{
+    fs as ufs,
+    logger::{LogLevel, Logger},
+    progress,
+};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tar::Builder as TarBuilder;
+
+mod cache;
+mod duplicates;
+mod export;
+mod identity;
+mod import;
+mod layers;
+mod lint;
+mod loops;
+mod playback;
+mod stage;
+
+pub use export::export_bank;
+pub use import::import_bank;
+pub use lint::lint_banks;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct BankSection {
+    name: String,
+    publisher: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    access: Option<String>,
+    #[serde(default)]
+    audio: Option<AudioTargetSection>,
+    #[serde(default)]
+    processing: Option<ProcessingSection>,
+    /// Read `v<N>`, `vel<N>` and `rr<N>` at the end of file names (`snare_v1_rr2.wav`)
+    /// as velocity layers and round-robin variants
+    #[serde(default)]
+    layer_suffixes: Option<bool>,
+    /// Read note names and three-digit numbers at the end of file names
+    /// (`piano_C4.wav`, `bass_036.wav`) as root notes
+    #[serde(default, alias = "midi_note_suffixes")]
+    note_suffixes: Option<bool>,
+    #[serde(default)]
+    duplicates: Option<DuplicatesSection>,
+}
+
+/// Target format of the packaged samples (`[bank.audio]`).
+///
+/// Unset fields keep the value of each source file.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct AudioTargetSection {
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    sample_rate: Option<u32>,
+    #[serde(default)]
+    bit_depth: Option<u16>,
+    #[serde(default)]
+    channels: Option<u16>,
+}
+
+/// Duplicate sample detection (`[bank.duplicates]`).
+///
+/// Identical samples are always reported.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct DuplicatesSection {
+    /// Also report near-duplicates, compared by audio fingerprint
+    #[serde(default)]
+    near: Option<bool>,
+    /// Similarity (0-1) from which two samples are near-duplicates
+    #[serde(default)]
+    threshold: Option<f64>,
+    /// Pack identical samples once and point every trigger at that copy
+    #[serde(default)]
+    dedupe: Option<bool>,
+}
+
+/// Sample processing applied when packaging (`[bank.processing]`, `[triggers.processing]`).
+///
+/// Trigger-level values override the bank-level ones field by field.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+struct ProcessingSection {
+    /// peak | loudness | none
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    normalize: Option<String>,
+    /// Target peak in dBFS, or target loudness in LUFS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target_db: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trim_silence: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    trim_threshold_db: Option<f64>,
+}
+
+/// Audio properties of a sample, probed at build time.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+struct SampleProps {
+    /// Duration in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    duration: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    sample_rate: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bit_depth: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    channels: Option<u16>,
+    /// Sample peak in dBFS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    peak_db: Option<f64>,
+    /// Hash of the decoded audio content
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+}
+
+impl From<&probe::AudioProbe> for SampleProps {
+    fn from(info: &probe::AudioProbe) -> Self {
+        SampleProps {
+            duration: Some(info.duration),
+            sample_rate: Some(info.sample_rate),
+            bit_depth: info.bit_depth,
+            channels: Some(info.channels),
+            peak_db: Some(info.peak_db),
+            hash: Some(info.hash.clone()),
+        }
+    }
+}
+
+/// A sample owned by a trigger (`[[triggers.layers]]`).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct TriggerLayer {
+    path: String,
+    /// Velocity range `[low, high]`, within 1-127
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    velocity: Option<[u8; 2]>,
+    /// Round-robin position within its velocity range (1-based)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    round_robin: Option<u32>,
+    /// MIDI note the sample plays at its original pitch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    root: Option<u8>,
+    /// Key range `[low, high]` of MIDI notes mapped to this sample
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    keys: Option<[u8; 2]>,
+    /// Fine tuning in cents
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tune: Option<i32>,
+    /// Loop start, in frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    loop_start: Option<u64>,
+    /// Loop end (exclusive), in frames
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    loop_end: Option<u64>,
+    #[serde(flatten)]
+    props: SampleProps,
+}
+
+impl TriggerLayer {
+    /// Fills the mapping fields left unset with the values of a discovered layer.
+    fn fill_from(&mut self, discovered: &TriggerLayer) {
+        self.velocity = self.velocity.or(discovered.velocity);
+        self.round_robin = self.round_robin.or(discovered.round_robin);
+        self.root = self.root.or(discovered.root);
+        self.keys = self.keys.or(discovered.keys);
+        self.tune = self.tune.or(discovered.tune);
+        self.loop_start = self.loop_start.or(discovered.loop_start);
+        self.loop_end = self.loop_end.or(discovered.loop_end);
+    }
+
+    /// Returns true if the layer carries any mapping information.
+    fn is_mapped(&self) -> bool {
+        self.velocity.is_some()
+            || self.round_robin.is_some()
+            || self.root.is_some()
+            || self.keys.is_some()
+            || self.tune.is_some()
+            || self.loop_start.is_some()
+            || self.loop_end.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct TriggerEntry {
+    name: String,
+    /// Human-readable name shown by the Forge UI
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    description: Option<String>,
+    /// Group of the trigger; defaults to its top-level folder under `audio/`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    /// Free-form labels (kick, snare, fx, one-shot, loop, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Tempo of a loop in BPM, overriding the estimate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bpm: Option<f64>,
+    /// Musical key of a loop (e.g. "A minor"), overriding the estimate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    /// Triggers sharing a choke group cut each other off (e.g. open and closed hi-hats)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    choke_group: Option<String>,
+    /// one_shot | gate | loop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mode: Option<String>,
+    /// Default gain in dB
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gain: Option<f64>,
+    /// Default pan, from -1 (left) to 1 (right)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pan: Option<f64>,
+    /// Maximum number of voices playing the trigger at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    polyphony: Option<u32>,
+    /// Default sample; the loudest layer when the trigger has layers
+    path: String,
+    #[serde(flatten)]
+    props: SampleProps,
+    /// Tempo estimated from the audio of a loop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detected_bpm: Option<f64>,
+    /// Key estimated from the audio of a loop
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    detected_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    processing: Option<ProcessingSection>,
+    /// Waveform preview, relative to the archive root (packaged manifest only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    preview: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    layers: Vec<TriggerLayer>,
+}
+
+impl TriggerEntry {
+    /// Returns the paths of every sample owned by the trigger.
+    fn sample_paths(&self) -> Vec<&str> {
+        let mut paths = vec![self.path.as_str()];
+        for l in &self.layers {
+            if !paths.contains(&l.path.as_str()) {
+                paths.push(&l.path);
+            }
+        }
+        paths
+    }
+
+    /// Returns the samples of the trigger as layers.
+    fn as_layers(&self) -> Vec<TriggerLayer> {
+        if !self.layers.is_empty() {
+            return self.layers.clone();
+        }
+        vec![TriggerLayer {
+            path: self.path.clone(),
+            props: self.props.clone(),
+            ..Default::default()
+        }]
+    }
+
+    /// Replaces the layers of the trigger and points its default sample at the
+    /// primary layer when the current one is no longer part of them.
+    fn set_layers(&mut self, mut layers: Vec<TriggerLayer>) {
+        layers::sort_layers(&mut layers);
+        let current = layers.iter().find(|l| l.path == self.path);
+        let primary = current.or_else(|| layers::primary_layer(&layers));
+        if let Some(p) = primary {
+            self.path = p.path.clone();
+            self.props = p.props.clone();
+        }
+        self.layers = layers;
+    }
+
+    /// Takes over the discovered samples assigned to this trigger.
+    ///
+    /// Mapping values already written in bank.toml (velocity, round-robin, keys,
+    /// tuning, loop points) are kept; unset ones are filled from discovery and
+    /// probed properties are refreshed.
+    fn absorb(mut self, discovered: Vec<TriggerLayer>) -> TriggerEntry {
+        if self.layers.is_empty() && discovered.len() == 1 && !discovered[0].is_mapped() {
+            self.path = discovered[0].path.clone();
+            self.props = discovered[0].props.clone();
+            return self;
+        }
+
+        // A sample can back several layers (e.g. different key or velocity ranges).
+        let mut merged: Vec<TriggerLayer> = Vec::new();
+        for d in discovered {
+            let existing: Vec<&TriggerLayer> =
+                self.layers.iter().filter(|l| l.path == d.path).collect();
+            if existing.is_empty() {
+                merged.push(d);
+                continue;
+            }
+            for e in existing {
+                let mut l = e.clone();
+                l.fill_from(&d);
+                l.props = d.props.clone();
+                merged.push(l);
+            }
+        }
+        self.set_layers(merged);
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct TriggersDoc<'a> {
+    triggers: &'a [TriggerEntry],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct BankToml {
+    bank: BankSection,
+    #[serde(default)]
+    triggers: Vec<TriggerEntry>,
+}
+
+/// Builds a bank located at the given path.
+///
+/// The bank is skipped, and its archive kept, when nothing changed in its directory
+/// since the last build, unless `force` is set.
+///
+/// ### Parameters
+/// - `path`: The path of the bank
+/// - `cwd`: The current working directory
+/// - `force`: Whether to rebuild the bank even if it is up to date
+///
+pub fn build_bank(path: &str, cwd: &str, force: bool) -> Result<(), String> {
+    build_bank_cached(path, cwd, force).map(|_| ())
+}
+
+/// Builds a bank unless it is up to date; returns false when it was skipped.
+fn build_bank_cached(path: &str, cwd: &str, force: bool) -> Result<bool, String> {
+    let bank_dir = resolve_bank_dir(cwd, path)?;
+    let (publisher, name) = read_bank_id(&bank_dir)?;
+
+    let out_root = Path::new(cwd).join("output").join("bank");
+    let out_file = out_root.join(format!("{}.{}.tar.gz", publisher, name));
+    let cache_file = cache::cache_path(cwd, &publisher, &name);
+    if !force && cache::is_up_to_date(&cache_file, &bank_dir, &out_file) {
+        progress::println(&format!(
+            "⏭️  Bank up to date: {}",
+            out_file.to_string_lossy()
+        ));
+        return Ok(false);
+    }
+
+    let (bank_toml_path, audio_dir, mut bank_doc, changes) = load_bank(&bank_dir)?;
+    identity::report_changes(&changes);
+    playback::warn_lonely_chokes(&bank_doc.triggers);
+    loops::analyze_loops(&audio_dir, &mut bank_doc.triggers)?;
+    warn_mixed_formats(&bank_doc.triggers);
+    duplicates::report_duplicates(
+        &audio_dir,
+        &bank_doc.triggers,
+        bank_doc.bank.duplicates.as_ref(),
+    )?;
+
+    write_triggers_after_bank(&bank_toml_path, &bank_doc.triggers)?;
+
+    fs::create_dir_all(&out_root)
+        .map_err(|e| format!("Failed to create output directory: {}", e))?;
+
+    let staging_dir = Path::new(cwd)
+        .join("output")
+        .join(".staging")
+        .join("bank")
+        .join(format!("{}.{}", publisher, name));
+    let result = package_bank(
+        &bank_dir,
+        &bank_toml_path,
+        &audio_dir,
+        &staging_dir,
+        &bank_doc,
+        &out_file,
+    );
+    if staging_dir.exists() {
+        let _ = fs::remove_dir_all(&staging_dir);
+    }
+    result?;
+    cache::store(&cache_file, &bank_dir, &out_file)?;
+    progress::println(&format!("✅ Bank built: {}", out_file.to_string_lossy()));
+
+    Ok(true)
+}
+
+/// Reads the publisher and name of a bank from its bank.toml.
+///
+/// ### Parameters
+/// - `bank_dir`: The bank directory
+///
+fn read_bank_id(bank_dir: &Path) -> Result<(String, String), String> {
+    let bank_toml_path = bank_dir.join("bank.toml");
+    if !bank_toml_path.exists() {
+        return Err(format!(
+            "bank.toml not found in: {}",
+            bank_dir.to_string_lossy()
+        ));
+    }
+    let txt = fs::read_to_string(&bank_toml_path)
+        .map_err(|e| format!("Failed to read bank.toml: {}", e))?;
+    let bank_doc: BankToml = toml::from_str(&txt).map_err(|e| format!("Invalid TOML: {}", e))?;
+
+    let publisher = bank_doc.bank.publisher;
+    let name = bank_doc.bank.name;
+    if publisher.trim().is_empty() || name.trim().is_empty() {
+        return Err("Fields [bank].publisher and [bank].name are required in bank.toml".into());
+    }
+    Ok((publisher, name))
+}
+
+/// Reads a bank manifest and merges its triggers with the samples discovered in `audio/`.
+///
+/// Returns the bank.toml path, the audio directory, the manifest with merged triggers
+/// and the trigger changes made by the merge.
+///
+/// ### Parameters
+/// - `bank_dir`: The bank directory
+///
+fn load_bank(
+    bank_dir: &Path,
+) -> Result<(PathBuf, PathBuf, BankToml, identity::TriggerChanges), String> {
+    let bank_toml_path = bank_dir.join("bank.toml");
+    if !bank_toml_path.exists() {
+        return Err(format!(
+            "bank.toml not found in: {}",
+            bank_dir.to_string_lossy()
+        ));
+    }
+
+    let mut bank_doc: BankToml = {
+        let txt = fs::read_to_string(&bank_toml_path)
+            .map_err(|e| format!("Failed to read bank.toml: {}", e))?;
+        toml::from_str(&txt).map_err(|e| format!("Invalid TOML: {}", e))?
+    };
+
+    let audio_dir = bank_dir.join("audio");
+    if !audio_dir.is_dir() {
+        return Err(format!(
+            "Audio directory not found: {}",
+            audio_dir.to_string_lossy()
+        ));
+    }
+
+    let layer_suffixes = bank_doc.bank.layer_suffixes.unwrap_or(false);
+    let note_suffixes = bank_doc.bank.note_suffixes.unwrap_or(false);
+    let discovered = discover_triggers(&audio_dir, layer_suffixes, note_suffixes)?;
+    let (triggers, changes) = merge_triggers(bank_doc.triggers, discovered);
+    playback::validate_playback(&triggers)?;
+    bank_doc.triggers = triggers;
+    Ok((bank_toml_path, audio_dir, bank_doc, changes))
+}
+
+/// Prepares the packaged audio and manifest of a bank, then writes its archive.
+///
+/// When `[bank.audio]` or any processing is set, samples are rendered into
+/// `staging_dir` and the packaged manifest points at the rendered files; the source
+/// `audio/` folder is never modified. A waveform preview is rendered for every
+/// packaged trigger.
+///
+/// ### Parameters
+/// - `bank_dir`: The path to the bank directory.
+/// - `bank_toml_path`: The path to the bank.toml file.
+/// - `audio_dir`: The path to the source audio directory.
+/// - `staging_dir`: A scratch directory for processed audio.
+/// - `bank_doc`: The parsed bank manifest, with merged triggers.
+/// - `out_file`: The output archive path.
+///
+fn package_bank(
+    bank_dir: &Path,
+    bank_toml_path: &Path,
+    audio_dir: &Path,
+    staging_dir: &Path,
+    bank_doc: &BankToml,
+    out_file: &Path,
+) -> Result<(), String> {
+    let mut packaged_audio_dir = audio_dir.to_path_buf();
+    let mut packaged_triggers = bank_doc.triggers.clone();
+    let mut rendered_previews = HashMap::new();
+
+    if stage::needs_staging(bank_doc) {
+        let staged_audio = staging_dir.join("audio");
+        (packaged_triggers, rendered_previews) =
+            stage::stage_audio(audio_dir, &staged_audio, bank_doc)?;
+        packaged_audio_dir = staged_audio;
+    }
+
+    let previews_dir = staging_dir.join("previews");
+    stage::stage_previews(
+        &packaged_audio_dir,
+        &previews_dir,
+        &mut packaged_triggers,
+        &rendered_previews,
+    )?;
+
+    let dedupe = bank_doc
+        .bank
+        .duplicates
+        .as_ref()
+        .and_then(|d| d.dedupe)
+        .unwrap_or(false);
+    let excluded = if dedupe {
+        duplicates::dedupe_packaged(&mut packaged_triggers)
+    } else {
+        HashSet::new()
+    };
+
+    loops::resolve_packaged(&mut packaged_triggers);
+
+    let source_manifest = fs::read_to_string(bank_toml_path)
+        .map_err(|e| format!("Failed to read bank.toml: {}", e))?;
+    let manifest = render_triggers_after_bank(&source_manifest, &packaged_triggers)?;
+
+    create_bank_tar_gz(
+        bank_dir,
+        &manifest,
+        &packaged_audio_dir,
+        &excluded,
+        &previews_dir,
+        out_file,
+        &bank_doc.bank,
+    )
+}
+
+/// Builds all banks in the generated directory.
+///
+/// Banks unchanged since their last build are skipped unless `force` is set. Up to
+/// `jobs` banks are built at once.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `force`: Whether to rebuild banks that are up to date
+/// - `jobs`: The maximum number of banks built at once
+///
+pub fn build_all_banks(cwd: &str, force: bool, jobs: usize) -> Result<(), String> {
+    let banks_root = Path::new(cwd).join("generated").join("banks");
+    if !banks_root.exists() {
+        return Err(format!(
+            "Banks directory not found: {}",
+            banks_root.to_string_lossy()
+        ));
+    }
+
+    // Discover bank directories by searching for bank.toml anywhere under generated/banks.
+    // Project layout uses generated/banks/<publisher>/<name>/bank.toml, so the previous
+    // implementation that only checked the first level missed nested banks.
+    let mut bank_dirs: Vec<PathBuf> = Vec::new();
+    let files = ufs::walk_files(&banks_root)
+        .map_err(|e| format!("Failed to traverse {}: {}", banks_root.to_string_lossy(), e))?;
+    for p in files {
+        if p.file_name()
+            .and_then(|f| f.to_str())
+            .map(|s| s.eq_ignore_ascii_case("bank.toml"))
+            .unwrap_or(false)
+            && let Some(parent) = p.parent()
+        {
+            bank_dirs.push(parent.to_path_buf());
+        }
+    }
+    // Deduplicate and sort
+    bank_dirs.sort();
+    bank_dirs.dedup();
+
+    if bank_dirs.is_empty() {
+        return Err("No banks to build (generated/banks is empty)".into());
+    }
+
+    bank_dirs.sort();
+
+    let mut errors: Vec<String> = Vec::new();
+    let mut skipped = 0usize;
+    let total = bank_dirs.len();
+    let outcomes = progress::run_jobs(
+        &bank_dirs,
+        jobs,
+        |p| {
+            ufs::path_relative_to(p, &banks_root)
+                .map(ufs::to_unix_string)
+                .unwrap_or_else(|| p.to_string_lossy().to_string())
+        },
+        |p| build_bank_cached(&p.to_string_lossy(), cwd, force),
+    );
+    for outcome in outcomes {
+        match outcome.result {
+            Ok(true) => {}
+            Ok(false) => skipped += 1,
+            Err(e) => errors.push(format!("{} -> {}", outcome.label, e)),
+        }
+    }
+
+    if errors.is_empty() {
+        if skipped > 0 {
+            println!(
+                "✅ Build complete: {} bank(s) built, {} up to date",
+                total - skipped,
+                skipped
+            );
+        } else {
+            println!("✅ Build complete: {} bank(s) built", total);
+        }
+        Ok(())
+    } else {
+        let joined = errors.join("\n - ");
+        Err(format!(
+            "Some banks failed ({}/{}):\n - {}",
+            errors.len(),
+            total,
+            joined
+        ))
+    }
+}
+
+/// Builds banks, then rebuilds a bank whenever its bank.toml, audio, README.md or
+/// LICENSE changes, until interrupted.
+///
+/// ### Parameters
+/// - `path`: The bank to watch, or None to watch every bank under `generated/banks`
+/// - `cwd`: The current working directory
+/// - `force`: Whether to rebuild up-to-date banks on the initial build
+/// - `jobs`: The maximum number of banks built at once on the initial build
+///
+pub fn watch_banks(path: Option<&str>, cwd: &str, force: bool, jobs: usize) -> Result<(), String> {
+    let banks_root = Path::new(cwd).join("generated").join("banks");
+    let only = match path {
+        Some(p) => Some(resolve_bank_dir(cwd, p)?),
+        None => None,
+    };
+
+    // A failing initial build is reported, the fix is picked up by the watcher.
+    let initial = match &only {
+        Some(dir) => build_bank(&dir.to_string_lossy(), cwd, force),
+        None => build_all_banks(cwd, force, jobs),
+    };
+    if let Err(e) = initial {
+        Logger::new().log_message(LogLevel::Error, &e);
+    }
+
+    watch::watch_addons(
+        &banks_root,
+        "bank.toml",
+        only.as_deref(),
+        |rel| {
+            let mut parts = rel.components();
+            match (parts.next(), parts.next()) {
+                (Some(first), None) => ["bank.toml", "README.md", "LICENSE"]
+                    .iter()
+                    .any(|f| first.as_os_str() == *f),
+                (Some(first), Some(_)) => first.as_os_str() == "audio" && decode::is_supported(rel),
+                _ => false,
+            }
+        },
+        |dir| build_bank(&dir.to_string_lossy(), cwd, false),
+    )
+}
+
+/// Resolves the bank directory from the given input path or alias.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `input`: The input path or alias
+///
+fn resolve_bank_dir(cwd: &str, input: &str) -> Result<PathBuf, String> {
+    let candidate = Path::new(cwd).join(input);
+    if candidate.is_file()
+        && candidate
+            .file_name()
+            .map(|f| f == "bank.toml")
+            .unwrap_or(false)
+    {
+        return Ok(candidate.parent().unwrap().to_path_buf());
+    }
+    if candidate.is_dir() && candidate.join("bank.toml").exists() {
+        return Ok(candidate);
+    }
+
+    if let Some(rest) = input.strip_prefix("bank.") {
+        let banks_root = Path::new(cwd).join("generated").join("banks");
+        let by_exact = banks_root.join(rest);
+        if by_exact.join("bank.toml").exists() {
+            return Ok(by_exact);
+        }
+        if !rest.contains('.')
+            && let Ok(read_dir) = fs::read_dir(&banks_root)
+        {
+            let mut matches: Vec<PathBuf> = Vec::new();
+            for e in read_dir.flatten() {
+                let p = e.path();
+                if p.is_dir()
+                    && let Some(name) = p.file_name().and_then(|s| s.to_str())
+                    && name.ends_with(&format!(".{rest}"))
+                    && p.join("bank.toml").exists()
+                {
+                    matches.push(p.clone());
+                }
+            }
+            return match matches.len() {
+                1 => Ok(matches.remove(0)),
+                0 => Err(format!(
+                    "No bank matched alias bank.{} under {}",
+                    rest,
+                    banks_root.to_string_lossy()
+                )),
+                _ => Err(format!(
+                    "Multiple banks matched bank.{}; use 'bank.<publisher>.<name>'",
+                    rest
+                )),
+            };
+        }
+        return Err(format!(
+            "Alias not found: {}; expected under {}",
+            input,
+            banks_root.to_string_lossy()
+        ));
+    }
+
+    Err(format!(
+        "Invalid path: {} (no bank.toml found)",
+        candidate.to_string_lossy()
+    ))
+}
+
+/// Discover audio triggers in the given directory.
+///
+/// Every discovered file is decoded so its duration, sample rate, bit depth,
+/// channel count and peak level can be recorded on the trigger. Files named with
+/// velocity / round-robin / root note suffixes (e.g. `snare_v1_rr2.wav`,
+/// `piano_C4.wav`) are grouped as the layers of a single trigger when the matching
+/// suffixes are enabled.
+///
+/// ### Parameters
+/// - `audio_dir`: The directory to search for audio files
+/// - `layer_suffixes`: Whether `v<N>`, `vel<N>` and `rr<N>` in file names are layer suffixes
+/// - `note_suffixes`: Whether note names and three-digit numbers in file names are root notes
+///
+fn discover_triggers(
+    audio_dir: &Path,
+    layer_suffixes: bool,
+    note_suffixes: bool,
+) -> Result<Vec<TriggerEntry>, String> {
+    let mut samples: Vec<layers::DiscoveredSample> = Vec::new();
+    let files = ufs::walk_files(audio_dir)?;
+    for p in files {
+        if !decode::is_supported(&p) {
+            continue;
+        }
+        let rel = ufs::path_relative_to(&p, audio_dir).unwrap_or_else(|| {
+            p.file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(PathBuf::new)
+        });
+        let rel_str = format!("./{}", ufs::to_unix_string(&rel));
+        let dir = rel.parent().map(ufs::to_unix_string).unwrap_or_default();
+        let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or("");
+        let info = probe::probe_file(&p)?;
+        samples.push(layers::DiscoveredSample {
+            path: rel_str,
+            stem: stem.to_string(),
+            dir,
+            tags: layers::parse_layer_stem(stem, layer_suffixes, note_suffixes),
+            props: SampleProps::from(&info),
+            sampler: chunks::read_sampler_info(&p)?,
+        });
+    }
+    let mut out = layers::group_samples(samples);
+    for t in out.iter_mut() {
+        t.category = default_category(&t.path);
+    }
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(out)
+}
+
+/// Returns the default category of a sample: its top-level folder under `audio/`.
+///
+/// ### Parameters
+/// - `rel_path_with_dot`: The relative path with a dot prefix.
+///
+fn default_category(rel_path_with_dot: &str) -> Option<String> {
+    let rel = rel_path_with_dot.trim_start_matches("./");
+    rel.split_once('/').map(|(dir, _)| dir.to_string())
+}
+
+/// Creates a ZIP archive of the bank directory.
+///
+/// ### Parameters
+/// - `bank_dir`: The path to the bank directory.
+/// - `manifest`: The packaged bank.toml content.
+/// - `audio_dir`: The path to the audio directory to package.
+/// - `excluded`: Sample paths (`./...`) left out of the archive.
+/// - `previews_dir`: The path to the waveform previews directory.
+/// - `out_file`: The output ZIP file path.
+/// - `bank`: The `[bank]` section (publisher, name, description).
+///
+fn create_bank_tar_gz(
+    bank_dir: &Path,
+    manifest: &str,
+    audio_dir: &Path,
+    excluded: &HashSet<String>,
+    previews_dir: &Path,
+    out_file: &Path,
+    bank: &BankSection,
+) -> Result<(), String> {
+    let publisher = bank.publisher.as_str();
+    let file =
+        fs::File::create(out_file).map_err(|e| format!("Failed to create output file: {}", e))?;
+    let enc = GzEncoder::new(file, Compression::default());
+    let mut tar = TarBuilder::new(enc);
+
+    // bank.toml
+    let mut header = tar::Header::new_gnu();
+    header
+        .set_path("bank.toml")
+        .map_err(|e| format!("Failed to set header path: {}", e))?;
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    tar.append(&header, manifest.as_bytes())
+        .map_err(|e| format!("Failed to add bank.toml to tar: {}", e))?;
+
+    // README.md (from bank dir if present, else default)
+    let readme_path = bank_dir.join("README.md");
+    if readme_path.exists() {
+        tar.append_path_with_name(&readme_path, "README.md")
+            .map_err(|e| format!("Failed to add README.md to tar: {}", e))?;
+    } else {
+        let readme = default_readme_bank(publisher, &bank.name, bank.description.as_deref());
+        let mut header = tar::Header::new_gnu();
+        header
+            .set_path("README.md")
+            .map_err(|e| format!("Failed to set header path: {}", e))?;
+        header.set_size(readme.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append(&header, readme.as_bytes())
+            .map_err(|e| format!("Failed to append README.md to tar: {}", e))?;
+    }
+
+    // LICENSE (from bank dir if present, else default MIT)
+    let license_path = bank_dir.join("LICENSE");
+    if license_path.exists() {
+        tar.append_path_with_name(&license_path, "LICENSE")
+            .map_err(|e| format!("Failed to add LICENSE to tar: {}", e))?;
+    } else {
+        let license = default_mit_license(publisher);
+        let mut header = tar::Header::new_gnu();
+        header
+            .set_path("LICENSE")
+            .map_err(|e| format!("Failed to set header path: {}", e))?;
+        header.set_size(license.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append(&header, license.as_bytes())
+            .map_err(|e| format!("Failed to append LICENSE to tar: {}", e))?;
+    }
+
+    // audio/ directory and contents, minus deduplicated copies
+    let mut audio_files = ufs::walk_files(audio_dir)?;
+    audio_files.sort();
+    for p in audio_files {
+        let rel = ufs::to_unix_string(ufs::path_relative_to(&p, audio_dir).unwrap_or_default());
+        if excluded.contains(&format!("./{}", rel)) {
+            continue;
+        }
+        tar.append_path_with_name(&p, format!("audio/{}", rel))
+            .map_err(|e| format!("Failed to add {} to tar: {}", rel, e))?;
+    }
+
+    // previews/ directory (waveform images)
+    if previews_dir.is_dir() {
+        tar.append_dir_all("previews", previews_dir)
+            .map_err(|e| format!("Failed to add previews dir to tar: {}", e))?;
+    }
+
+    // Finish writing tar and gzip
+    let enc = tar
+        .into_inner()
+        .map_err(|e| format!("Failed to finish tar builder: {}", e))?;
+    enc.finish()
+        .map_err(|e| format!("Failed to finish gzip encoder: {}", e))?;
+
+    let _ = fs::metadata(out_file).map_err(|e| format!("Failed to stat tar.gz: {}", e))?;
+    Ok(())
+}
+
+/// Gets the default README.md for a bank.
+///
+/// ### Parameters
+/// - `author`: The author of the bank.
+/// - `name`: The name of the bank.
+/// - `description`: The description of the bank.
+///
+fn default_readme_bank(publisher: &str, name: &str, description: Option<&str>) -> String {
+    let desc = description.unwrap_or("Sample bank for Devalang.");
+    format!(
+        "# {}.{} Bank\n\n{}\n\nContents:\n- bank.toml\n- audio/ (assets)\n- previews/ (waveforms)\n- LICENSE\n\nBuilt with devaforge.\n",
+        publisher, name, desc
+    )
+}
+
+/// Gets the default LICENSE for a bank.
+///
+/// ### Parameters
+/// - `publisher`: The publisher of the bank.
+///
+fn default_mit_license(publisher: &str) -> String {
+    format!(
+        "MIT License\n\nCopyright (c) {}\n\nPermission is hereby granted, free of charge, to any person obtaining a copy\n of this software and associated documentation files (the \"Software\"), to deal\n in the Software without restriction, including without limitation the rights\n to use, copy, modify, merge, publish, distribute, sublicense, and/or sell\n copies of the Software, and to permit persons to whom the Software is\n furnished to do so, subject to the following conditions:\n\nThe above copyright notice and this permission notice shall be included in all\n copies or substantial portions of the Software.\n\nTHE SOFTWARE IS PROVIDED \"AS IS\", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR\n IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,\n FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE\n AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER\n LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,\n OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE\n SOFTWARE.\n",
+        publisher
+    )
+}
+
+/// Writes bank's triggers after the `[bank]` section.
+///
+/// ### Parameters
+/// - `bank_toml_path`: The path to the bank.toml file.
+/// - `triggers`: The triggers to add to the bank.toml file.
+///
+fn write_triggers_after_bank(
+    bank_toml_path: &Path,
+    triggers: &[TriggerEntry],
+) -> Result<(), String> {
+    let original = fs::read_to_string(bank_toml_path)
+        .map_err(|e| format!("Failed to read bank.toml: {}", e))?;
+    let result = render_triggers_after_bank(&original, triggers)?;
+    if result == original {
+        return Ok(());
+    }
+    fs::write(bank_toml_path, result).map_err(|e| format!("Failed to write bank.toml: {}", e))?;
+    Ok(())
+}
+
+/// Renders a bank.toml document with its triggers replaced, right after the `[bank]` section.
+///
+/// ### Parameters
+/// - `original`: The bank.toml content.
+/// - `triggers`: The triggers to write.
+///
+fn render_triggers_after_bank(original: &str, triggers: &[TriggerEntry]) -> Result<String, String> {
+    let mut cleaned: Vec<String> = Vec::new();
+    let mut skipping_triggers = false;
+    for line in original.lines() {
+        let trimmed = line.trim();
+        if !skipping_triggers {
+            if is_triggers_header(trimmed) {
+                skipping_triggers = true;
+                continue;
+            }
+            cleaned.push(line.to_string());
+        } else if trimmed.starts_with('[') && !is_triggers_header(trimmed) {
+            skipping_triggers = false;
+            cleaned.push(line.to_string());
+        } else {
+            continue;
+        }
+    }
+
+    let mut insert_idx = cleaned.len();
+    let mut in_bank = false;
+    for (i, line) in cleaned.iter().enumerate() {
+        let t = line.trim();
+        if t == "[bank]" || t.starts_with("[bank.") {
+            in_bank = true;
+            insert_idx = i + 1;
+            continue;
+        }
+        if in_bank && t.starts_with('[') {
+            insert_idx = i;
+            break;
+        }
+        if in_bank && !t.is_empty() {
+            insert_idx = i + 1;
+        }
+    }
+
+    let head = cleaned[..insert_idx].to_vec();
+    let mut tail = cleaned[insert_idx..].to_vec();
+    while !tail.is_empty() && tail[0].trim().is_empty() {
+        tail.remove(0);
+    }
+
+    let mut trig_lines: Vec<String> = Vec::new();
+    if !triggers.is_empty() {
+        let rendered = toml::to_string(&TriggersDoc { triggers })
+            .map_err(|e| format!("Failed to serialize triggers: {}", e))?;
+        trig_lines.extend(rendered.trim_end().lines().map(|l| l.to_string()));
+    }
+
+    let mut result_lines: Vec<String> = Vec::new();
+    result_lines.extend(head);
+    if !trig_lines.is_empty() {
+        result_lines.push(String::new());
+        result_lines.extend(trig_lines);
+        if !tail.is_empty() {
+            result_lines.push(String::new());
+        }
+    }
+    result_lines.extend(tail);
+
+    let mut result = result_lines.join("\n");
+    if !result.ends_with('\n') {
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+/// Returns true for `[[triggers]]` headers and their sub-tables (e.g. `[triggers.processing]`).
+///
+/// ### Parameters
+/// - `line`: The trimmed line.
+///
+fn is_triggers_header(line: &str) -> bool {
+    line == "[[triggers]]" || line.starts_with("[triggers.") || line.starts_with("[[triggers.")
+}
+
+/// Merges the existing and discovered triggers.
+///
+/// Samples moved or renamed in `audio/` are followed by content hash so their
+/// trigger keeps its name. Returns the merged triggers and the changes made.
+///
+/// ### Parameters
+/// - `existing`: The existing triggers.
+/// - `discovered`: The discovered triggers.
+///
+fn merge_triggers(
+    mut existing: Vec<TriggerEntry>,
+    discovered: Vec<TriggerEntry>,
+) -> (Vec<TriggerEntry>, identity::TriggerChanges) {
+    use std::collections::HashMap;
+    let moved = identity::follow_moves(&mut existing, &discovered);
+    let previous: Vec<String> = existing.iter().map(|t| t.name.clone()).collect();
+    let mut used_names: HashSet<String> = existing.iter().map(|t| t.name.clone()).collect();
+
+    // Every sample referenced by an existing trigger (default path or layer) stays
+    // with it, including samples shared by several triggers.
+    let mut claimed: HashMap<String, Vec<usize>> = HashMap::new();
+    for (i, t) in existing.iter().enumerate() {
+        for p in t.sample_paths() {
+            claimed.entry(p.to_string()).or_default().push(i);
+        }
+    }
+
+    let mut absorbed: Vec<Vec<TriggerLayer>> = vec![Vec::new(); existing.len()];
+    let mut fresh: Vec<TriggerEntry> = Vec::new();
+    for mut d in discovered {
+        let layers = d.as_layers();
+        let total = layers.len();
+        let mut unclaimed: Vec<TriggerLayer> = Vec::new();
+        for layer in layers {
+            match claimed.get(&layer.path) {
+                Some(owners) => {
+                    for i in owners {
+                        absorbed[*i].push(layer.clone());
+                    }
+                }
+                None => unclaimed.push(layer),
+            }
+        }
+        if unclaimed.is_empty() {
+            continue;
+        }
+        if unclaimed.len() == 1 && !unclaimed[0].is_mapped() {
+            d.path = unclaimed[0].path.clone();
+            d.props = unclaimed[0].props.clone();
+            d.layers.clear();
+        } else if unclaimed.len() != total {
+            d.set_layers(unclaimed);
+        }
+        d.name = disambiguate_name(&d.name, &d.path, &mut used_names);
+        fresh.push(d);
+    }
+
+    // Existing entries keep their name and user-set fields (a category left unset is
+    // filled from the folder); triggers whose samples are all gone are dropped.
+    let mut final_triggers: Vec<TriggerEntry> = existing
+        .into_iter()
+        .zip(absorbed)
+        .filter(|(_, samples)| !samples.is_empty())
+        .map(|(t, samples)| {
+            let mut t = t.absorb(samples);
+            t.category = t.category.or_else(|| default_category(&t.path));
+            t
+        })
+        .collect();
+    final_triggers.extend(fresh);
+    final_triggers.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let changes = identity::TriggerChanges {
+        moved,
+        added: final_triggers
+            .iter()
+            .filter(|t| !previous.contains(&t.name))
+            .map(|t| t.name.clone())
+            .collect(),
+        removed: previous
+            .into_iter()
+            .filter(|name| !final_triggers.iter().any(|t| t.name == *name))
+            .collect(),
+    };
+    (final_triggers, changes)
+}
+
+/// Warns when the triggers of a bank do not share the same sample rate or bit depth.
+///
+/// ### Parameters
+/// - `triggers`: The triggers of the bank.
+///
+fn warn_mixed_formats(triggers: &[TriggerEntry]) {
+    use std::collections::BTreeMap;
+    let mut rates: BTreeMap<u32, Vec<&str>> = BTreeMap::new();
+    let mut depths: BTreeMap<u16, Vec<&str>> = BTreeMap::new();
+    for t in triggers {
+        let mut props: Vec<&SampleProps> = vec![&t.props];
+        props.extend(t.layers.iter().map(|l| &l.props));
+        for sr in props.iter().filter_map(|p| p.sample_rate) {
+            let names = rates.entry(sr).or_default();
+            if !names.contains(&t.name.as_str()) {
+                names.push(&t.name);
+            }
+        }
+        for bd in props.iter().filter_map(|p| p.bit_depth) {
+            let names = depths.entry(bd).or_default();
+            if !names.contains(&t.name.as_str()) {
+                names.push(&t.name);
+            }
+        }
+    }
+
+    let logger = Logger::new();
+    if rates.len() > 1 {
+        let trace: Vec<String> = rates
+            .iter()
+            .map(|(sr, names)| format!("{} Hz: {}", sr, names.join(", ")))
+            .collect();
+        logger.log_message_with_trace(
+            LogLevel::Warning,
+            "Bank mixes several sample rates:",
+            trace.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+    if depths.len() > 1 {
+        let trace: Vec<String> = depths
+            .iter()
+            .map(|(bd, names)| format!("{}-bit: {}", bd, names.join(", ")))
+            .collect();
+        logger.log_message_with_trace(
+            LogLevel::Warning,
+            "Bank mixes several bit depths:",
+            trace.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+}
+
+/// Disambiguates a name to ensure uniqueness within the used set.
+///
+/// ### Parameters
+/// - `base`: The base name to disambiguate.
+/// - `rel_path_with_dot`: The relative path with a dot prefix.
+/// - `used`: The set of already used names.
+///
+fn disambiguate_name(
+    base: &str,
+    rel_path_with_dot: &str,
+    used: &mut std::collections::HashSet<String>,
+) -> String {
+    if !base.is_empty() && !used.contains(base) {
+        used.insert(base.to_string());
+        return base.to_string();
+    }
+    let rel = rel_path_with_dot.trim_start_matches("./");
+    let mut parts: Vec<&str> = rel.split('/').collect();
+    if parts.len() > 1 {
+        parts.pop();
+        let joined = format!("{}.{}", parts.join("."), base);
+        if !used.contains(&joined) {
+            used.insert(joined.clone());
+            return joined;
+        }
+        let mut acc: Vec<&str> = Vec::new();
+        for comp in parts.iter().rev() {
+            acc.push(comp);
+            let name = format!(
+                "{}.{}",
+                acc.iter().rev().cloned().collect::<Vec<&str>>().join("."),
+                base
+            );
+            if !used.contains(&name) {
+                used.insert(name.clone());
+                return name;
+            }
+        }
+    }
+    let mut i = 2usize;
+    loop {
+        let cand = format!("{}_{}", base, i);
+        if !used.contains(&cand) {
+            used.insert(cand.clone());
+            return cand;
+        }
+        i += 1;
+    }
+}
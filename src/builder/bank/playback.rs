@@ -0,0 +1,92 @@
+use super::TriggerEntry;
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::BTreeMap;
+
+/// Playback modes accepted in `mode`.
+pub(super) const MODES: [&str; 3] = ["one_shot", "gate", "loop"];
+
+/// Validates the playback settings of every trigger.
+///
+/// ### Parameters
+/// - `triggers`: The triggers of the bank
+///
+pub(super) fn validate_playback(triggers: &[TriggerEntry]) -> Result<(), String> {
+    for t in triggers {
+        if let Some(mode) = &t.mode
+            && !MODES.contains(&mode.as_str())
+        {
+            return Err(format!(
+                "Invalid mode of trigger '{}': {} (expected: {})",
+                t.name,
+                mode,
+                MODES.join("|")
+            ));
+        }
+        if let Some(gain) = t.gain
+            && !(-96.0..=24.0).contains(&gain)
+        {
+            return Err(format!(
+                "Invalid gain of trigger '{}': {} (expected -96..24 dB)",
+                t.name, gain
+            ));
+        }
+        if let Some(pan) = t.pan
+            && !(-1.0..=1.0).contains(&pan)
+        {
+            return Err(format!(
+                "Invalid pan of trigger '{}': {} (expected -1..1)",
+                t.name, pan
+            ));
+        }
+        if t.polyphony == Some(0) {
+            return Err(format!(
+                "Invalid polyphony of trigger '{}': 0 (must be >= 1)",
+                t.name
+            ));
+        }
+        if let Some(group) = &t.choke_group
+            && group.trim().is_empty()
+        {
+            return Err(format!("Empty choke_group on trigger '{}'", t.name));
+        }
+    }
+    Ok(())
+}
+
+/// Returns the choke groups holding a single trigger, with that trigger's name.
+///
+/// ### Parameters
+/// - `triggers`: The triggers of the bank
+///
+pub(super) fn lonely_choke_groups(triggers: &[TriggerEntry]) -> Vec<(&str, &str)> {
+    let mut chokes: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for t in triggers {
+        if let Some(group) = &t.choke_group {
+            chokes.entry(group).or_default().push(&t.name);
+        }
+    }
+    chokes
+        .into_iter()
+        .filter(|(_, names)| names.len() == 1)
+        .map(|(group, names)| (group, names[0]))
+        .collect()
+}
+
+/// Warns about choke groups holding a single trigger, which have no effect.
+///
+/// ### Parameters
+/// - `triggers`: The triggers of the bank
+///
+pub(super) fn warn_lonely_chokes(triggers: &[TriggerEntry]) {
+    let lonely: Vec<String> = lonely_choke_groups(triggers)
+        .iter()
+        .map(|(group, name)| format!("{}: {}", group, name))
+        .collect();
+    if !lonely.is_empty() {
+        Logger::new().log_message_with_trace(
+            LogLevel::Warning,
+            "Choke groups with a single trigger have no effect:",
+            lonely.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+}
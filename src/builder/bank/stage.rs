@@ -0,0 +1,451 @@
+use super::{AudioTargetSection, BankToml, ProcessingSection, SampleProps, TriggerEntry};
+use crate::audio::{
+    decode::{self, DecodedAudio},
+    encode, probe, process, resample, waveform,
+};
+use crate::utils::fs as ufs;
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_PEAK_TARGET_DB: f64 = -1.0;
+const DEFAULT_LOUDNESS_TARGET_LUFS: f64 = -16.0;
+const DEFAULT_TRIM_THRESHOLD_DB: f64 = -60.0;
+
+/// Returns true when the packaged audio differs from the source `audio/` folder.
+///
+/// ### Parameters
+/// - `bank_doc`: The parsed bank manifest.
+///
+pub(super) fn needs_staging(bank_doc: &BankToml) -> bool {
+    bank_doc.bank.audio.is_some()
+        || bank_doc.bank.processing.is_some()
+        || bank_doc.triggers.iter().any(|t| t.processing.is_some())
+}
+
+/// Renders the packaged audio of a bank into a staging directory.
+///
+/// Samples that need neither transcoding nor processing are copied unchanged, as
+/// are non-audio files. Rendered samples are written as WAV. Returns the triggers
+/// updated with the packaged paths and properties, and the waveform preview of every
+/// rendered sample by packaged path, drawn from the audio decoded here.
+///
+/// ### Parameters
+/// - `audio_dir`: The source audio directory.
+/// - `staged_audio_dir`: The directory receiving the packaged files.
+/// - `bank_doc`: The parsed bank manifest, with merged triggers.
+///
+pub(super) fn stage_audio(
+    audio_dir: &Path,
+    staged_audio_dir: &Path,
+    bank_doc: &BankToml,
+) -> Result<(Vec<TriggerEntry>, HashMap<String, String>), String> {
+    let target = bank_doc.bank.audio.as_ref();
+    if let Some(t) = target {
+        validate_audio_target(t)?;
+    }
+    if let Some(p) = &bank_doc.bank.processing {
+        validate_processing(p, "[bank.processing]")?;
+    }
+    for t in &bank_doc.triggers {
+        if let Some(p) = &t.processing {
+            validate_processing(p, &format!("processing of trigger '{}'", t.name))?;
+        }
+    }
+
+    if staged_audio_dir.exists() {
+        fs::remove_dir_all(staged_audio_dir)
+            .map_err(|e| format!("Failed to clean staging directory: {}", e))?;
+    }
+    fs::create_dir_all(staged_audio_dir)
+        .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+
+    // A sample is packaged once, so every trigger using it must process it the same way.
+    let mut processing_by_path: HashMap<&str, (&str, ProcessingSection)> = HashMap::new();
+    for t in &bank_doc.triggers {
+        let effective =
+            overlay_processing(bank_doc.bank.processing.as_ref(), t.processing.as_ref());
+        for p in t.sample_paths() {
+            match processing_by_path.get(p) {
+                Some((other, processing)) if *processing != effective => {
+                    return Err(format!(
+                        "Triggers '{}' and '{}' both use {} with different processing; give them the same [triggers.processing] or separate files",
+                        other, t.name, p
+                    ));
+                }
+                Some(_) => {}
+                None => {
+                    processing_by_path.insert(p, (&t.name, effective.clone()));
+                }
+            }
+        }
+    }
+
+    let mut rendered: HashMap<String, Rendered> = HashMap::new();
+    let mut previews: HashMap<String, String> = HashMap::new();
+    let mut written: HashSet<PathBuf> = HashSet::new();
+    for p in ufs::walk_files(audio_dir)? {
+        let rel = ufs::path_relative_to(&p, audio_dir).unwrap_or_else(|| {
+            p.file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(PathBuf::new)
+        });
+        let key = format!("./{}", ufs::to_unix_string(&rel));
+        let processing = processing_by_path
+            .get(key.as_str())
+            .map(|(_, p)| p.clone())
+            .unwrap_or_default();
+        let render = decode::is_supported(&p) && (target.is_some() || is_active(&processing));
+
+        let out_rel = if render {
+            rel.with_extension("wav")
+        } else {
+            rel.clone()
+        };
+        if !written.insert(out_rel.clone()) {
+            return Err(format!(
+                "Rendering would overwrite {}; rename one of the source files",
+                ufs::to_unix_string(&out_rel)
+            ));
+        }
+
+        let out_path = staged_audio_dir.join(&out_rel);
+        if let Some(parent) = out_path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create staging directory: {}", e))?;
+        }
+
+        if !render {
+            fs::copy(&p, &out_path)
+                .map_err(|e| format!("Failed to copy {}: {}", p.to_string_lossy(), e))?;
+            continue;
+        }
+
+        let mut audio = decode::decode_file(&p)?;
+        let source_rate = audio.sample_rate;
+        if let Some(t) = target {
+            audio = convert(&audio, t);
+        }
+        let (processed, offset) = apply_processing(&audio, &processing);
+        audio = processed;
+
+        let bit_depth = target
+            .and_then(|t| t.bit_depth)
+            .or(audio.bit_depth)
+            .filter(|b| encode::SUPPORTED_BIT_DEPTHS.contains(b))
+            .unwrap_or(24);
+        audio.bit_depth = Some(bit_depth);
+        encode::write_wav(&audio, bit_depth, &out_path)?;
+
+        let packaged_path = format!("./{}", ufs::to_unix_string(&out_rel));
+        previews.insert(
+            packaged_path.clone(),
+            waveform::render_svg(&audio, waveform::PREVIEW_WIDTH, waveform::PREVIEW_HEIGHT),
+        );
+        rendered.insert(
+            key,
+            Rendered {
+                path: packaged_path,
+                props: SampleProps::from(&probe::probe_decoded(&audio)),
+                rate_ratio: audio.sample_rate as f64 / source_rate.max(1) as f64,
+                offset: offset as u64,
+                frames: audio.frames() as u64,
+            },
+        );
+    }
+
+    let mut out = bank_doc.triggers.clone();
+    for t in out.iter_mut() {
+        if let Some(r) = rendered.get(&t.path) {
+            t.path = r.path.clone();
+            t.props = r.props.clone();
+        }
+        for l in t.layers.iter_mut() {
+            if let Some(r) = rendered.get(&l.path) {
+                l.path = r.path.clone();
+                l.props = r.props.clone();
+                l.loop_start = l.loop_start.map(|f| r.map_frame(f));
+                l.loop_end = l.loop_end.map(|f| r.map_frame(f));
+            }
+        }
+    }
+    Ok((out, previews))
+}
+
+/// A sample rendered into the staging directory.
+struct Rendered {
+    path: String,
+    props: SampleProps,
+    /// Output sample rate divided by source sample rate
+    rate_ratio: f64,
+    /// Leading frames removed by trimming, at the output rate
+    offset: u64,
+    /// Frame count of the rendered sample
+    frames: u64,
+}
+
+impl Rendered {
+    /// Maps a frame position of the source sample to the rendered sample.
+    fn map_frame(&self, frame: u64) -> u64 {
+        let scaled = (frame as f64 * self.rate_ratio).round() as u64;
+        scaled.saturating_sub(self.offset).min(self.frames)
+    }
+}
+
+/// Renders a waveform preview for every trigger into `previews_dir`.
+///
+/// Previews are named after the trigger (see [`preview_file_stem`]) and referenced
+/// from its `preview` field, relative to the archive root. Samples rendered by
+/// staging reuse the preview drawn from their decoded audio; others are decoded
+/// once here. A sample that cannot be decoded gets no preview, without failing the
+/// build.
+///
+/// ### Parameters
+/// - `audio_dir`: The packaged audio directory.
+/// - `previews_dir`: The directory receiving the previews.
+/// - `triggers`: The packaged triggers, updated in place.
+/// - `rendered`: Previews already drawn by staging, by packaged path.
+///
+pub(super) fn stage_previews(
+    audio_dir: &Path,
+    previews_dir: &Path,
+    triggers: &mut [TriggerEntry],
+    rendered: &HashMap<String, String>,
+) -> Result<(), String> {
+    if previews_dir.exists() {
+        fs::remove_dir_all(previews_dir)
+            .map_err(|e| format!("Failed to clean previews directory: {}", e))?;
+    }
+    fs::create_dir_all(previews_dir)
+        .map_err(|e| format!("Failed to create previews directory: {}", e))?;
+
+    let logger = Logger::new();
+    let mut svgs: HashMap<String, Option<String>> = HashMap::new();
+    let mut used: HashSet<String> = HashSet::new();
+    for t in triggers.iter_mut() {
+        let svg = svgs.entry(t.path.clone()).or_insert_with(|| {
+            if let Some(svg) = rendered.get(&t.path) {
+                return Some(svg.clone());
+            }
+            let sample = audio_dir.join(t.path.trim_start_matches("./"));
+            match decode::decode_file(&sample) {
+                Ok(audio) => Some(waveform::render_svg(
+                    &audio,
+                    waveform::PREVIEW_WIDTH,
+                    waveform::PREVIEW_HEIGHT,
+                )),
+                Err(e) => {
+                    logger.log_message(
+                        LogLevel::Warning,
+                        &format!("No preview for trigger '{}': {}", t.name, e),
+                    );
+                    None
+                }
+            }
+        });
+        let Some(svg) = svg else {
+            continue;
+        };
+
+        // Names that only differ by case or punctuation share a stem; number the later ones.
+        let stem = preview_file_stem(&t.name);
+        let mut file_name = format!("{}.svg", stem);
+        let mut n = 2;
+        while !used.insert(file_name.clone()) {
+            file_name = format!("{}-{}.svg", stem, n);
+            n += 1;
+        }
+
+        fs::write(previews_dir.join(&file_name), svg.as_bytes())
+            .map_err(|e| format!("Failed to write preview {}: {}", file_name, e))?;
+        t.preview = Some(format!("previews/{}", file_name));
+    }
+    Ok(())
+}
+
+/// Returns the file stem of a trigger preview: the trigger name in lowercase, with
+/// any run of characters other than letters, digits, `-` and `_` replaced by `-`.
+///
+/// Lowercasing keeps names that differ only by case apart on case-insensitive
+/// filesystems. Falls back to `trigger` when nothing usable is left.
+///
+/// ### Parameters
+/// - `name`: The trigger name.
+///
+fn preview_file_stem(name: &str) -> String {
+    let mut stem = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+            stem.push(c.to_ascii_lowercase());
+        } else if !stem.ends_with('-') {
+            stem.push('-');
+        }
+    }
+    let stem = stem.trim_matches('-');
+    if stem.is_empty() {
+        "trigger".to_string()
+    } else {
+        stem.to_string()
+    }
+}
+
+/// Converts audio to the channel count and sample rate of the target format.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio.
+/// - `target`: The target format.
+///
+fn convert(audio: &DecodedAudio, target: &AudioTargetSection) -> DecodedAudio {
+    let mut audio = audio.clone();
+    let channels = target.channels.unwrap_or(audio.channels);
+    // Downmix before resampling and upmix after, so the resampler runs on fewer channels.
+    if channels < audio.channels {
+        audio = resample::remix(&audio, channels);
+    }
+    if let Some(rate) = target.sample_rate {
+        audio = resample::resample(&audio, rate);
+    }
+    if channels > audio.channels {
+        audio = resample::remix(&audio, channels);
+    }
+    audio
+}
+
+/// Trims and normalizes audio according to the processing settings.
+///
+/// Returns the processed audio and the number of leading frames trimmed.
+///
+/// ### Parameters
+/// - `audio`: The decoded audio.
+/// - `processing`: The effective processing settings.
+///
+fn apply_processing(audio: &DecodedAudio, processing: &ProcessingSection) -> (DecodedAudio, usize) {
+    let mut audio = audio.clone();
+    let mut offset = 0;
+    if processing.trim_silence.unwrap_or(false) {
+        let threshold = processing
+            .trim_threshold_db
+            .unwrap_or(DEFAULT_TRIM_THRESHOLD_DB);
+        (audio, offset) = process::trim_silence(&audio, threshold);
+    }
+    match processing.normalize.as_deref() {
+        Some("peak") => {
+            let target = processing.target_db.unwrap_or(DEFAULT_PEAK_TARGET_DB);
+            audio = process::normalize_peak(&audio, target);
+        }
+        Some("loudness") => {
+            let target = processing.target_db.unwrap_or(DEFAULT_LOUDNESS_TARGET_LUFS);
+            audio = process::normalize_loudness(&audio, target);
+        }
+        _ => {}
+    }
+    (audio, offset)
+}
+
+/// Overlays trigger-level processing settings on the bank-level ones.
+///
+/// ### Parameters
+/// - `bank`: The `[bank.processing]` settings.
+/// - `trigger`: The `[triggers.processing]` settings.
+///
+fn overlay_processing(
+    bank: Option<&ProcessingSection>,
+    trigger: Option<&ProcessingSection>,
+) -> ProcessingSection {
+    let base = bank.cloned().unwrap_or_default();
+    let Some(t) = trigger else {
+        return base;
+    };
+    ProcessingSection {
+        normalize: t.normalize.clone().or(base.normalize),
+        target_db: t.target_db.or(base.target_db),
+        trim_silence: t.trim_silence.or(base.trim_silence),
+        trim_threshold_db: t.trim_threshold_db.or(base.trim_threshold_db),
+    }
+}
+
+/// Returns true if the processing settings change the audio.
+///
+/// ### Parameters
+/// - `processing`: The effective processing settings.
+///
+fn is_active(processing: &ProcessingSection) -> bool {
+    processing.trim_silence.unwrap_or(false)
+        || matches!(processing.normalize.as_deref(), Some("peak" | "loudness"))
+}
+
+/// Validates processing settings.
+///
+/// ### Parameters
+/// - `processing`: The processing settings.
+/// - `context`: Where the settings were declared, for error messages.
+///
+fn validate_processing(processing: &ProcessingSection, context: &str) -> Result<(), String> {
+    if let Some(mode) = &processing.normalize
+        && !matches!(mode.as_str(), "peak" | "loudness" | "none")
+    {
+        return Err(format!(
+            "Invalid normalize mode in {}: {} (expected: peak|loudness|none)",
+            context, mode
+        ));
+    }
+    if let Some(target) = processing.target_db
+        && target > 0.0
+    {
+        return Err(format!(
+            "Invalid target_db in {}: {} (must be <= 0)",
+            context, target
+        ));
+    }
+    if let Some(threshold) = processing.trim_threshold_db
+        && threshold > 0.0
+    {
+        return Err(format!(
+            "Invalid trim_threshold_db in {}: {} (must be <= 0)",
+            context, threshold
+        ));
+    }
+    Ok(())
+}
+
+/// Validates the `[bank.audio]` target format.
+///
+/// ### Parameters
+/// - `target`: The target format.
+///
+fn validate_audio_target(target: &AudioTargetSection) -> Result<(), String> {
+    if let Some(format) = &target.format
+        && !format.eq_ignore_ascii_case("wav")
+    {
+        return Err(format!(
+            "Unsupported [bank.audio].format: {} (expected: wav)",
+            format
+        ));
+    }
+    if let Some(rate) = target.sample_rate
+        && !(8_000..=192_000).contains(&rate)
+    {
+        return Err(format!(
+            "Invalid [bank.audio].sample_rate: {} (expected 8000..192000)",
+            rate
+        ));
+    }
+    if let Some(depth) = target.bit_depth
+        && !encode::SUPPORTED_BIT_DEPTHS.contains(&depth)
+    {
+        return Err(format!(
+            "Invalid [bank.audio].bit_depth: {} (expected: 8|16|24|32)",
+            depth
+        ));
+    }
+    if let Some(channels) = target.channels
+        && !(1..=8).contains(&channels)
+    {
+        return Err(format!(
+            "Invalid [bank.audio].channels: {} (expected 1..8)",
+            channels
+        ));
+    }
+    Ok(())
+}
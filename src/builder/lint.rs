@@ -0,0 +1,329 @@
+use crate::utils::{
+    fs as ufs,
+    logger::{LogLevel, Logger},
+};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Access levels accepted in `[bank]` / `[plugin]`.
+pub const ACCESS_LEVELS: [&str; 3] = ["public", "private", "protected"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in an addon.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable identifier of the check, e.g. `invalid-trigger-name`
+    pub code: &'static str,
+    /// File the problem is about, relative to the addon directory
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    pub message: String,
+}
+
+impl Finding {
+    pub fn error(code: &'static str, file: Option<String>, message: String) -> Self {
+        Finding {
+            severity: Severity::Error,
+            code,
+            file,
+            message,
+        }
+    }
+
+    pub fn warning(code: &'static str, file: Option<String>, message: String) -> Self {
+        Finding {
+            severity: Severity::Warning,
+            code,
+            file,
+            message,
+        }
+    }
+}
+
+/// Findings of one addon.
+#[derive(Debug, Clone, Serialize)]
+pub struct AddonReport {
+    /// `bank` or `plugin`
+    pub kind: &'static str,
+    /// `<publisher>.<name>`, or the directory when the manifest cannot be read
+    pub id: String,
+    pub path: String,
+    pub findings: Vec<Finding>,
+}
+
+#[derive(Serialize)]
+struct LintSummary<'a> {
+    addons: &'a [AddonReport],
+    errors: usize,
+    warnings: usize,
+}
+
+/// Output format of the lint commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintFormat {
+    Human,
+    Json,
+}
+
+impl LintFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.to_ascii_lowercase().as_str() {
+            "human" => Ok(LintFormat::Human),
+            "json" => Ok(LintFormat::Json),
+            other => Err(format!(
+                "Unknown lint format: {} (expected: human|json)",
+                other
+            )),
+        }
+    }
+}
+
+/// Returns the addon directories to lint: the one given, or every directory holding
+/// `manifest` under `root`.
+///
+/// ### Parameters
+/// - `root`: The directory holding the addons
+/// - `manifest`: The manifest file name (`bank.toml`, `plugin.toml`)
+/// - `only`: A single addon directory, already resolved
+///
+pub fn addon_dirs(
+    root: &Path,
+    manifest: &str,
+    only: Option<PathBuf>,
+) -> Result<Vec<PathBuf>, String> {
+    if let Some(dir) = only {
+        return Ok(vec![dir]);
+    }
+    if !root.exists() {
+        return Err(format!("Directory not found: {}", root.to_string_lossy()));
+    }
+    let mut dirs: Vec<PathBuf> = ufs::walk_files(root)?
+        .into_iter()
+        .filter(|p| p.file_name().map(|f| f == manifest).unwrap_or(false))
+        .filter_map(|p| p.parent().map(Path::to_path_buf))
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    Ok(dirs)
+}
+
+/// Checks the fields shared by `[bank]` and `[plugin]`: publisher, name, version and access.
+///
+/// ### Parameters
+/// - `section`: The manifest section name (`bank`, `plugin`)
+/// - `publisher`: The publisher
+/// - `name`: The addon name
+/// - `version`: The version, if set
+/// - `access`: The access level, if set
+///
+pub fn check_manifest(
+    section: &str,
+    publisher: &str,
+    name: &str,
+    version: Option<&str>,
+    access: Option<&str>,
+) -> Vec<Finding> {
+    let manifest = Some(format!("{}.toml", section));
+    let mut findings = Vec::new();
+
+    if publisher.trim().is_empty() {
+        findings.push(Finding::error(
+            "missing-publisher",
+            manifest.clone(),
+            format!("[{}].publisher is missing", section),
+        ));
+    } else if !is_kebab_case(publisher) {
+        findings.push(Finding::error(
+            "publisher-not-kebab-case",
+            manifest.clone(),
+            format!(
+                "Publisher '{}' is not kebab-case (lowercase letters and digits separated by '-')",
+                publisher
+            ),
+        ));
+    }
+    if name.trim().is_empty() {
+        findings.push(Finding::error(
+            "missing-name",
+            manifest.clone(),
+            format!("[{}].name is missing", section),
+        ));
+    }
+
+    match version {
+        None => findings.push(Finding::error(
+            "missing-version",
+            manifest.clone(),
+            format!("[{}].version is missing", section),
+        )),
+        Some(v) if !is_semver(v) => findings.push(Finding::error(
+            "invalid-version",
+            manifest.clone(),
+            format!("Version '{}' is not <major>.<minor>.<patch>", v),
+        )),
+        Some(_) => {}
+    }
+
+    if let Some(a) = access
+        && !ACCESS_LEVELS.contains(&a)
+    {
+        findings.push(Finding::error(
+            "unknown-access",
+            manifest,
+            format!(
+                "Unknown access '{}' (expected: {})",
+                a,
+                ACCESS_LEVELS.join(" | ")
+            ),
+        ));
+    }
+    findings
+}
+
+/// Warns about a missing README.md or LICENSE in an addon directory.
+///
+/// ### Parameters
+/// - `addon_dir`: The addon directory
+///
+pub fn check_docs(addon_dir: &Path) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    if !addon_dir.join("README.md").is_file() {
+        findings.push(Finding::warning(
+            "missing-readme",
+            None,
+            "README.md is missing; a generic one is packaged instead".to_string(),
+        ));
+    }
+    if !addon_dir.join("LICENSE").is_file() {
+        findings.push(Finding::warning(
+            "missing-license",
+            None,
+            "LICENSE is missing; the default MIT license is packaged instead".to_string(),
+        ));
+    }
+    findings
+}
+
+/// Prints lint reports, then fails if any of them holds an error.
+///
+/// ### Parameters
+/// - `reports`: The reports, one per addon
+/// - `format`: The output format
+///
+pub fn print_reports(reports: &[AddonReport], format: LintFormat) -> Result<(), String> {
+    let count = |severity: Severity| {
+        reports
+            .iter()
+            .flat_map(|r| &r.findings)
+            .filter(|f| f.severity == severity)
+            .count()
+    };
+    let errors = count(Severity::Error);
+    let warnings = count(Severity::Warning);
+
+    match format {
+        LintFormat::Json => {
+            let summary = LintSummary {
+                addons: reports,
+                errors,
+                warnings,
+            };
+            let json = serde_json::to_string_pretty(&summary)
+                .map_err(|e| format!("Failed to serialize lint report: {}", e))?;
+            println!("{}", json);
+        }
+        LintFormat::Human => {
+            let logger = Logger::new();
+            for report in reports {
+                if report.findings.is_empty() {
+                    logger.log_message(
+                        LogLevel::Success,
+                        &format!("{} {}: no problems found", report.kind, report.id),
+                    );
+                    continue;
+                }
+                let level = if report
+                    .findings
+                    .iter()
+                    .any(|f| f.severity == Severity::Error)
+                {
+                    LogLevel::Error
+                } else {
+                    LogLevel::Warning
+                };
+                let lines: Vec<String> = report
+                    .findings
+                    .iter()
+                    .map(|f| {
+                        let severity = match f.severity {
+                            Severity::Error => "error",
+                            Severity::Warning => "warning",
+                        };
+                        match &f.file {
+                            Some(file) => {
+                                format!("{}[{}] {}: {}", severity, f.code, file, f.message)
+                            }
+                            None => format!("{}[{}] {}", severity, f.code, f.message),
+                        }
+                    })
+                    .collect();
+                logger.log_message_with_trace(
+                    level,
+                    &format!("{} {} ({})", report.kind, report.id, report.path),
+                    lines.iter().map(|s| s.as_str()).collect(),
+                );
+            }
+            logger.log_message(
+                LogLevel::Info,
+                &format!(
+                    "{} error(s), {} warning(s) in {} addon(s)",
+                    errors,
+                    warnings,
+                    reports.len()
+                ),
+            );
+        }
+    }
+
+    if errors > 0 {
+        Err(format!("Lint failed with {} error(s)", errors))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns true for lowercase kebab-case: `acme`, `acme-audio-2`.
+fn is_kebab_case(value: &str) -> bool {
+    !value.is_empty()
+        && value.split('-').all(|part| {
+            !part.is_empty()
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        })
+}
+
+/// Returns true for `<major>.<minor>.<patch>`, with an optional `-<pre-release>` suffix.
+fn is_semver(value: &str) -> bool {
+    let (base, pre) = match value.split_once('-') {
+        Some((base, pre)) => (base, Some(pre)),
+        None => (value, None),
+    };
+    let parts: Vec<&str> = base.split('.').collect();
+    parts.len() == 3
+        && parts
+            .iter()
+            .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+        && pre.is_none_or(|p| {
+            !p.is_empty()
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        })
+}
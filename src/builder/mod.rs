@@ -1,2 +1,4 @@
 pub mod bank;
+pub mod lint;
 pub mod plugin;
+pub mod watch;
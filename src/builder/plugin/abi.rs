@@ -0,0 +1,336 @@
+use crate::builder::lint::{Finding, Severity};
+use crate::utils::logger::{LogLevel, Logger};
+use std::collections::BTreeMap;
+use wasmparser::{ExternalKind, Parser, Payload, TypeRef};
+
+/// Value types a host can pass to plugin functions.
+const NUMERIC_TYPES: [&str; 4] = ["i32", "i64", "f32", "f64"];
+
+/// Parameter and result types of an exported function, as recorded in `plugin.toml`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct FuncSignature {
+    pub params: Vec<String>,
+    pub results: Vec<String>,
+}
+
+/// Highest initial memory accepted, in 64 KiB pages (64 MiB).
+const MAX_INITIAL_PAGES: u64 = 1024;
+
+/// Checks that a plugin module can be loaded by Devalang: it imports nothing, defines
+/// and exports a single 32-bit memory of reasonable size, and has no start function.
+///
+/// Devalang provides no host functions: plugins are instantiated without imports,
+/// buffers are passed through the exported memory and every export is called directly.
+///
+/// ### Parameters
+/// - `wasm`: The module bytes
+/// - `wasm_name`: The module file, reported in the findings
+///
+pub(super) fn check_module(wasm: &[u8], wasm_name: &str) -> Vec<Finding> {
+    let wasm_file = || Some(wasm_name.to_string());
+    let mut findings = Vec::new();
+    let mut memories = 0usize;
+    let mut exports_memory = false;
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        let payload = match payload {
+            Ok(p) => p,
+            Err(e) => {
+                findings.push(Finding::error(
+                    "invalid-wasm",
+                    wasm_file(),
+                    format!("Cannot parse the module: {}", e),
+                ));
+                return findings;
+            }
+        };
+        match payload {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let Some(import) = read_entry(import, &mut findings, wasm_name) else {
+                        return findings;
+                    };
+                    let kind = match import.ty {
+                        TypeRef::Func(_) => "function",
+                        TypeRef::Memory(_) => {
+                            memories += 1;
+                            "memory"
+                        }
+                        TypeRef::Table(_) => "table",
+                        TypeRef::Global(_) => "global",
+                        TypeRef::Tag(_) => "tag",
+                    };
+                    findings.push(Finding::error(
+                        "forbidden-import",
+                        wasm_file(),
+                        format!(
+                            "Imports {} {}::{}, which Devalang does not provide{}",
+                            kind,
+                            import.module,
+                            import.name,
+                            import_hint(import.module)
+                        ),
+                    ));
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    let Some(memory) = read_entry(memory, &mut findings, wasm_name) else {
+                        return findings;
+                    };
+                    memories += 1;
+                    if memory.memory64 {
+                        findings.push(Finding::error(
+                            "memory64",
+                            wasm_file(),
+                            "Uses a 64-bit memory; plugins must use 32-bit addresses".to_string(),
+                        ));
+                    }
+                    if memory.shared {
+                        findings.push(Finding::error(
+                            "shared-memory",
+                            wasm_file(),
+                            "Uses a shared memory, which requires threads".to_string(),
+                        ));
+                    }
+                    if memory.initial > MAX_INITIAL_PAGES {
+                        findings.push(Finding::error(
+                            "memory-too-large",
+                            wasm_file(),
+                            format!(
+                                "Starts with {} pages of memory ({} MiB), more than the {} MiB allowed",
+                                memory.initial,
+                                memory.initial / 16,
+                                MAX_INITIAL_PAGES / 16
+                            ),
+                        ));
+                    }
+                    if let Some(max) = memory.maximum {
+                        findings.push(Finding::warning(
+                            "memory-maximum",
+                            wasm_file(),
+                            format!(
+                                "Limits its memory to {} pages ({} KiB); the host cannot grow it for large buffers",
+                                max,
+                                max * 64
+                            ),
+                        ));
+                    }
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let Some(export) = read_entry(export, &mut findings, wasm_name) else {
+                        return findings;
+                    };
+                    exports_memory |=
+                        export.kind == ExternalKind::Memory && export.name == "memory";
+                }
+            }
+            Payload::StartSection { func, .. } => {
+                findings.push(Finding::error(
+                    "start-function",
+                    wasm_file(),
+                    format!(
+                        "Declares a start function (#{}), which would run code when the plugin is loaded",
+                        func
+                    ),
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    if memories > 1 {
+        findings.push(Finding::error(
+            "multiple-memories",
+            wasm_file(),
+            format!(
+                "Declares {} memories; plugins must use a single one",
+                memories
+            ),
+        ));
+    }
+    if !exports_memory {
+        findings.push(Finding::error(
+            "memory-not-exported",
+            wasm_file(),
+            "Does not export its memory as 'memory'; Devalang cannot pass it audio buffers"
+                .to_string(),
+        ));
+    }
+    findings
+}
+
+/// Returns a section entry, or records why it cannot be read as an `invalid-wasm` error.
+///
+/// ### Parameters
+/// - `entry`: The entry read from a section
+/// - `findings`: The findings to add the error to
+/// - `wasm_name`: The module file, reported in the finding
+///
+fn read_entry<T>(
+    entry: Result<T, wasmparser::BinaryReaderError>,
+    findings: &mut Vec<Finding>,
+    wasm_name: &str,
+) -> Option<T> {
+    match entry {
+        Ok(entry) => Some(entry),
+        Err(e) => {
+            findings.push(Finding::error(
+                "invalid-wasm",
+                Some(wasm_name.to_string()),
+                format!("Cannot parse the module: {}", e),
+            ));
+            None
+        }
+    }
+}
+
+/// Fails with every error of a plugin module check, after logging its warnings.
+///
+/// ### Parameters
+/// - `wasm`: The module bytes
+/// - `wasm_name`: The module file name, for messages
+///
+pub(super) fn validate_module(wasm: &[u8], wasm_name: &str) -> Result<(), String> {
+    let findings = check_module(wasm, wasm_name);
+    let logger = Logger::new();
+    for warning in findings.iter().filter(|f| f.severity == Severity::Warning) {
+        logger.log_message(
+            LogLevel::Warning,
+            &format!("{}: {}", wasm_name, warning.message),
+        );
+    }
+    let errors: Vec<String> = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .map(|f| format!("[{}] {}", f.code, f.message))
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} cannot be loaded by Devalang:\n - {}",
+            wasm_name,
+            errors.join("\n - ")
+        ))
+    }
+}
+
+/// Returns how to get rid of imports from well-known modules.
+fn import_hint(module: &str) -> &'static str {
+    if module.starts_with("wasi") {
+        " (built against WASI: target wasm32-unknown-unknown and avoid std I/O, time and env)"
+    } else if module.contains("wbindgen") || module == "wbg" {
+        " (wasm-bindgen glue: plugins must not depend on wasm-bindgen)"
+    } else {
+        ""
+    }
+}
+
+/// Returns the signature of every exported function, by export name.
+///
+/// ### Parameters
+/// - `wasm`: The module bytes
+///
+pub(super) fn export_signatures(wasm: &[u8]) -> Result<BTreeMap<String, FuncSignature>, String> {
+    let invalid = |e: wasmparser::BinaryReaderError| format!("Invalid WASM module: {}", e);
+    let mut types: Vec<FuncSignature> = Vec::new();
+    // Type of every function by index: imported functions first, then defined ones.
+    let mut func_types: Vec<u32> = Vec::new();
+    let mut exports: Vec<(String, u32)> = Vec::new();
+
+    for payload in Parser::new(0).parse_all(wasm) {
+        match payload.map_err(invalid)? {
+            Payload::TypeSection(reader) => {
+                for ty in reader.into_iter_err_on_gc_types() {
+                    let ty = ty.map_err(invalid)?;
+                    types.push(FuncSignature {
+                        params: ty.params().iter().map(|t| t.to_string()).collect(),
+                        results: ty.results().iter().map(|t| t.to_string()).collect(),
+                    });
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let TypeRef::Func(ty) = import.map_err(invalid)?.ty {
+                        func_types.push(ty);
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    func_types.push(ty.map_err(invalid)?);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(invalid)?;
+                    if export.kind == ExternalKind::Func {
+                        exports.push((export.name.to_string(), export.index));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    exports
+        .into_iter()
+        .map(|(name, index)| {
+            let signature = func_types
+                .get(index as usize)
+                .and_then(|ty| types.get(*ty as usize))
+                .cloned()
+                .ok_or_else(|| format!("Export '{}' has no function type", name))?;
+            Ok((name, signature))
+        })
+        .collect()
+}
+
+/// Checks an exported function against the shape the host calls it with.
+///
+/// - setters (`set_*`) take a single numeric value: `(value) -> ()`
+/// - processing functions take a buffer then numeric arguments:
+///   `(ptr: i32, len: i32, ...args) -> ()`
+///
+/// ### Parameters
+/// - `name`: The export name
+/// - `signature`: The export signature
+///
+pub(super) fn check_signature(name: &str, signature: &FuncSignature) -> Result<(), String> {
+    let is_numeric = |t: &String| NUMERIC_TYPES.contains(&t.as_str());
+    let (valid, expected) = if name.starts_with("set_") {
+        (
+            signature.params.len() == 1 && signature.params.iter().all(is_numeric),
+            "(value: i32 | i64 | f32 | f64) -> ()",
+        )
+    } else {
+        (
+            signature.params.len() >= 2
+                && signature.params[..2].iter().all(|t| t == "i32")
+                && signature.params[2..].iter().all(is_numeric),
+            "(ptr: i32, len: i32, ...args: i32 | i64 | f32 | f64) -> ()",
+        )
+    };
+    if valid && signature.results.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} has type {}, expected {}",
+            name,
+            format_signature(signature),
+            expected
+        ))
+    }
+}
+
+/// Formats a signature as `(i32, i32, f32) -> ()`.
+pub(super) fn format_signature(signature: &FuncSignature) -> String {
+    format!(
+        "({}) -> ({})",
+        signature.params.join(", "),
+        signature.results.join(", ")
+    )
+}
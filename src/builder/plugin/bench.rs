@@ -0,0 +1,217 @@
+use super::render::select_function;
+use super::runtime::{self, BufferFunction, PluginRuntime};
+use crate::audio::signal;
+use crate::utils::logger::{LogLevel, Logger};
+use std::time::Duration;
+
+/// Buffer sizes benchmarked, in frames.
+const BUFFER_SIZES: [usize; 6] = [64, 128, 256, 512, 1024, 2048];
+
+/// Sample rates the real-time budget is computed for, in Hz.
+const SAMPLE_RATES: [u32; 2] = [44100, 48000];
+
+/// Load above which a warning is printed when no `max_load` is set, in percent.
+const WARN_LOAD: f64 = 100.0;
+
+/// Settings of a plugin benchmark.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Exported function to benchmark; all buffer functions when omitted
+    pub function: Option<String>,
+    /// Values of the function arguments following the buffer; each defaults to 1
+    pub args: Vec<f64>,
+    /// Timed calls per buffer size
+    pub iterations: usize,
+    /// Highest accepted load, in percent of the real-time budget; loads are only
+    /// reported when omitted
+    pub max_load: Option<f64>,
+}
+
+/// Timing of one function on one buffer size.
+struct BufferTiming {
+    frames: usize,
+    median: Duration,
+    worst: Duration,
+}
+
+/// Benchmarks the buffer functions of a built plugin against the real-time budget.
+///
+/// Every function runs on buffers of 64 to 2048 frames in an embedded WebAssembly
+/// interpreter. The median time per buffer is reported as a percentage of the buffer
+/// duration at 44.1 and 48 kHz, along with the memory the plugin grows while
+/// processing. Interpreter timings are well above those of a compiled runtime, so
+/// they compare plugins with each other rather than predict their real cost. Fails
+/// when a function exceeds `max_load`, if set, at any size or rate; otherwise loads
+/// above 100% only produce a warning.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `id`: The plugin identifier: `<publisher>.<name>`
+/// - `options`: The benchmark settings
+///
+pub fn bench_plugin(cwd: &str, id: &str, options: &BenchOptions) -> Result<(), String> {
+    if options.iterations == 0 {
+        return Err("Invalid iterations: 0".to_string());
+    }
+    if let Some(max_load) = options.max_load
+        && (max_load.is_nan() || max_load <= 0.0)
+    {
+        return Err(format!("Invalid max load: {} (must be > 0)", max_load));
+    }
+    let threshold = options.max_load.unwrap_or(WARN_LOAD);
+    let archive = runtime::archive_path(cwd, id)?;
+    let wasm = runtime::read_archive_wasm(&archive)?;
+    let functions = {
+        let plugin = PluginRuntime::load(&wasm)?;
+        let all = plugin.buffer_functions();
+        match &options.function {
+            Some(name) => vec![select_function(&all, Some(name))?],
+            None if all.is_empty() => {
+                return Err(
+                    "The plugin exports no buffer function (ptr: i32, len: i32, ...args)"
+                        .to_string(),
+                );
+            }
+            None => all,
+        }
+    };
+
+    let logger = Logger::new();
+    logger.log_message(
+        LogLevel::Info,
+        "Timings come from the embedded WebAssembly interpreter: use them to compare plugins, not as the cost under Devalang's runtime",
+    );
+    let mut over_budget: Vec<String> = Vec::new();
+    for function in &functions {
+        let args = if options.args.is_empty() {
+            vec![1.0; function.args.len()]
+        } else {
+            options.args.clone()
+        };
+        // A fresh instance per function, so memory growth is not shared between them.
+        let mut plugin = PluginRuntime::load(&wasm)?;
+        let (timings, growth) = bench_function(&mut plugin, function, &args, options.iterations)?;
+
+        let mut lines: Vec<String> = Vec::new();
+        let mut worst_load = 0.0f64;
+        for timing in &timings {
+            let loads: Vec<f64> = SAMPLE_RATES
+                .iter()
+                .map(|sr| load_percent(timing.median, timing.frames, *sr))
+                .collect();
+            for (load, sr) in loads.iter().zip(SAMPLE_RATES) {
+                worst_load = worst_load.max(*load);
+                if *load > threshold {
+                    over_budget.push(format!(
+                        "{} at {} frames / {} Hz: {:.1}%",
+                        function.name, timing.frames, sr, load
+                    ));
+                }
+            }
+            lines.push(format!(
+                "{:>4} frames: {:>9.1} µs (worst {:.1} µs) | {:>6.2}% @ 44.1 kHz | {:>6.2}% @ 48 kHz",
+                timing.frames,
+                micros(timing.median),
+                micros(timing.worst),
+                loads[0],
+                loads[1]
+            ));
+        }
+        lines.push(if growth == 0 {
+            "Memory: no growth while processing".to_string()
+        } else {
+            format!(
+                "Memory: grew by {} KiB while processing (allocations in the audio path)",
+                growth / 1024
+            )
+        });
+
+        let level = if worst_load > threshold && options.max_load.is_some() {
+            LogLevel::Error
+        } else if worst_load > threshold || growth > 0 {
+            LogLevel::Warning
+        } else {
+            LogLevel::Success
+        };
+        let arg_list: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        logger.log_message_with_trace(
+            level,
+            &format!(
+                "{}({}): up to {:.2}% of the real-time budget",
+                function.name,
+                arg_list.join(", "),
+                worst_load
+            ),
+            lines.iter().map(|s| s.as_str()).collect(),
+        );
+    }
+
+    match options.max_load {
+        Some(max_load) if !over_budget.is_empty() => Err(format!(
+            "{} exceeds the max load of {}% in the interpreter:\n - {}",
+            id,
+            max_load,
+            over_budget.join("\n - ")
+        )),
+        None if !over_budget.is_empty() => {
+            logger.log_message_with_trace(
+                LogLevel::Warning,
+                &format!(
+                    "{} exceeds {}% of the real-time budget in the interpreter; pass --max-load to fail on it",
+                    id, WARN_LOAD
+                ),
+                over_budget.iter().map(|s| s.as_str()).collect(),
+            );
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Times a buffer function on every buffer size.
+///
+/// Returns the timings and the memory grown by the plugin during the timed calls, in bytes.
+///
+/// ### Parameters
+/// - `plugin`: The plugin
+/// - `function`: The buffer function
+/// - `args`: The values of the arguments following the buffer
+/// - `iterations`: The number of timed calls per buffer size
+///
+fn bench_function(
+    plugin: &mut PluginRuntime,
+    function: &BufferFunction,
+    args: &[f64],
+    iterations: usize,
+) -> Result<(Vec<BufferTiming>, usize), String> {
+    let mut timings = Vec::new();
+    let mut growth = 0usize;
+    for frames in BUFFER_SIZES {
+        let duration = frames as f64 / 48000.0;
+        let input = signal::generate("noise", 440.0, duration, 48000)?.samples;
+        // Warm-up: reserves the buffer region and lets the plugin set up its state.
+        plugin.process(function, &mut input.clone(), args)?;
+
+        let before = plugin.memory_size();
+        let mut times = plugin.time_calls(function, &input, args, iterations)?;
+        growth += plugin.memory_size().saturating_sub(before);
+
+        times.sort();
+        timings.push(BufferTiming {
+            frames,
+            median: times[times.len() / 2],
+            worst: times[times.len() - 1],
+        });
+    }
+    Ok((timings, growth))
+}
+
+/// Returns the share of the buffer duration spent processing it, in percent.
+fn load_percent(elapsed: Duration, frames: usize, sample_rate: u32) -> f64 {
+    let budget = frames as f64 / sample_rate as f64;
+    elapsed.as_secs_f64() / budget * 100.0
+}
+
+fn micros(d: Duration) -> f64 {
+    d.as_secs_f64() * 1e6
+}
@@ -0,0 +1,366 @@
+use super::render::{process_channels, select_function};
+use super::runtime::{self, PluginRuntime};
+use super::{PluginTomlDoc, resolve_plugin_dir};
+use crate::audio::{
+    decode::{self, DecodedAudio},
+    encode, signal,
+};
+use crate::builder::lint;
+use crate::utils::{
+    fs as ufs,
+    logger::{LogLevel, Logger},
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Default maximum difference between an output sample and the expected one.
+const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// A test case, read from `tests/<case>.toml`.
+#[derive(Debug, Deserialize)]
+struct TestCase {
+    /// Exported function to run; may be omitted when the plugin exports a single one
+    #[serde(default)]
+    function: Option<String>,
+    /// Values of the function arguments following the buffer
+    #[serde(default)]
+    args: Vec<f64>,
+    #[serde(default)]
+    input: TestInput,
+    #[serde(default)]
+    expect: TestExpect,
+}
+
+/// Signal processed by a test case: an audio file, or a generated signal.
+#[derive(Debug, Deserialize, Default)]
+struct TestInput {
+    /// Audio file, relative to `tests/`
+    #[serde(default)]
+    file: Option<String>,
+    #[serde(default)]
+    signal: Option<String>,
+    #[serde(default)]
+    freq: Option<f64>,
+    #[serde(default)]
+    duration: Option<f64>,
+    #[serde(default)]
+    sample_rate: Option<u32>,
+}
+
+/// Expected output of a test case: a WAV file, or a checksum of the output samples.
+#[derive(Debug, Deserialize, Default)]
+struct TestExpect {
+    /// Expected WAV file, relative to `tests/`
+    #[serde(default)]
+    file: Option<String>,
+    /// SHA-256 of the output samples (little-endian `f32`)
+    #[serde(default)]
+    checksum: Option<String>,
+    /// Maximum difference per sample when comparing to `file`
+    #[serde(default)]
+    tolerance: Option<f64>,
+}
+
+/// Runs the golden-audio test cases of plugins and reports pass/fail per case.
+///
+/// Every `tests/*.toml` file of a plugin declares one case, run on the plugin built in
+/// `output/plugin/` with a fresh instance. With `update`, the expected WAV files and
+/// checksums are (re)written from the current output instead of being compared.
+///
+/// ### Parameters
+/// - `path`: The plugin to test, or None to test every plugin with a `tests/` folder
+/// - `cwd`: The current working directory
+/// - `update`: Whether to record the current output as the expected one
+///
+pub fn test_plugins(path: Option<&str>, cwd: &str, update: bool) -> Result<(), String> {
+    let plugins_root = Path::new(cwd).join("generated").join("plugins");
+    let only = match path {
+        Some(p) => Some(resolve_plugin_dir(cwd, p)?),
+        None => None,
+    };
+    let explicit = only.is_some();
+    let dirs: Vec<PathBuf> = lint::addon_dirs(&plugins_root, "plugin.toml", only)?
+        .into_iter()
+        .filter(|d| explicit || d.join("tests").is_dir())
+        .collect();
+    if dirs.is_empty() {
+        return Err("No plugin with a tests/ folder".into());
+    }
+
+    let logger = Logger::new();
+    let mut passed = 0usize;
+    let mut failed: Vec<String> = Vec::new();
+    for dir in dirs {
+        let (id, results) = match run_plugin_cases(&dir, cwd, update) {
+            Ok(r) => r,
+            Err(e) => {
+                let label = dir.to_string_lossy().to_string();
+                logger.log_message(LogLevel::Error, &format!("{}: {}", label, e));
+                failed.push(label);
+                continue;
+            }
+        };
+        for (case, result) in results {
+            match result {
+                Ok(detail) => {
+                    passed += 1;
+                    logger.log_message(LogLevel::Success, &format!("{} {} ({})", id, case, detail));
+                }
+                Err(e) => {
+                    logger.log_message(LogLevel::Error, &format!("{} {}: {}", id, case, e));
+                    failed.push(format!("{} {}", id, case));
+                }
+            }
+        }
+    }
+
+    logger.log_message(
+        LogLevel::Info,
+        &format!("{} passed, {} failed", passed, failed.len()),
+    );
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Some plugin tests failed:\n - {}",
+            failed.join("\n - ")
+        ))
+    }
+}
+
+type CaseResults = Vec<(String, Result<String, String>)>;
+
+/// Runs every test case of one plugin.
+///
+/// Returns the plugin id and the outcome of each case, by case name.
+///
+/// ### Parameters
+/// - `plugin_dir`: The plugin directory
+/// - `cwd`: The current working directory
+/// - `update`: Whether to record the current output as the expected one
+///
+fn run_plugin_cases(
+    plugin_dir: &Path,
+    cwd: &str,
+    update: bool,
+) -> Result<(String, CaseResults), String> {
+    let doc: PluginTomlDoc = fs::read_to_string(plugin_dir.join("plugin.toml"))
+        .map_err(|e| format!("Failed to read plugin.toml: {}", e))
+        .and_then(|txt| toml::from_str(&txt).map_err(|e| format!("Invalid TOML: {}", e)))?;
+    let id = format!("{}.{}", doc.plugin.publisher, doc.plugin.name);
+    let archive = runtime::archive_path(cwd, &id)?;
+    warn_if_stale(plugin_dir, &archive);
+    let wasm = runtime::read_archive_wasm(&archive)?;
+
+    let tests_dir = plugin_dir.join("tests");
+    let mut case_files: Vec<PathBuf> = fs::read_dir(&tests_dir)
+        .map_err(|e| format!("Failed to read {}: {}", tests_dir.to_string_lossy(), e))?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "toml"))
+        .collect();
+    case_files.sort();
+    if case_files.is_empty() {
+        return Err(format!(
+            "No test case (*.toml) in {}",
+            tests_dir.to_string_lossy()
+        ));
+    }
+
+    let results = case_files
+        .iter()
+        .map(|file| {
+            let case = file
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (case, run_case(file, &tests_dir, &wasm, update))
+        })
+        .collect();
+    Ok((id, results))
+}
+
+/// Runs one test case and checks its output.
+///
+/// Returns a short description of the result when the case passes.
+///
+/// ### Parameters
+/// - `case_file`: The test case file
+/// - `tests_dir`: The plugin's `tests/` folder
+/// - `wasm`: The plugin module
+/// - `update`: Whether to record the current output as the expected one
+///
+fn run_case(
+    case_file: &Path,
+    tests_dir: &Path,
+    wasm: &[u8],
+    update: bool,
+) -> Result<String, String> {
+    let text = fs::read_to_string(case_file)
+        .map_err(|e| format!("Failed to read {}: {}", case_file.to_string_lossy(), e))?;
+    let case: TestCase = toml::from_str(&text).map_err(|e| format!("Invalid test case: {}", e))?;
+
+    let mut audio = match &case.input.file {
+        Some(file) => decode::decode_file(&tests_dir.join(file))?,
+        None => signal::generate(
+            case.input.signal.as_deref().unwrap_or("sine"),
+            case.input.freq.unwrap_or(440.0),
+            case.input.duration.unwrap_or(1.0),
+            case.input.sample_rate.unwrap_or(44100),
+        )?,
+    };
+    let mut plugin = PluginRuntime::load(wasm)?;
+    let function = select_function(&plugin.buffer_functions(), case.function.as_deref())?;
+    process_channels(&mut plugin, &function, &mut audio, &case.args)?;
+    let checksum = output_checksum(&audio);
+
+    match (&case.expect.file, &case.expect.checksum) {
+        (Some(file), _) => {
+            let expected_path = tests_dir.join(file);
+            if update {
+                encode::write_wav(&audio, 32, &expected_path)?;
+                return Ok(format!("recorded {}", file));
+            }
+            if !expected_path.is_file() {
+                return Err(format!(
+                    "Expected output {} not found; record it with --update",
+                    file
+                ));
+            }
+            let expected = decode::decode_file(&expected_path)?;
+            let tolerance = case.expect.tolerance.unwrap_or(DEFAULT_TOLERANCE);
+            compare(&audio, &expected, tolerance)
+        }
+        (None, Some(expected)) => {
+            if update {
+                if *expected != checksum {
+                    fs::write(case_file, replace_checksum(&text, &checksum)).map_err(|e| {
+                        format!("Failed to write {}: {}", case_file.to_string_lossy(), e)
+                    })?;
+                }
+                return Ok("recorded checksum".to_string());
+            }
+            if expected.eq_ignore_ascii_case(&checksum) {
+                Ok("checksum matches".to_string())
+            } else {
+                Err(format!(
+                    "Checksum mismatch: expected {}, got {}",
+                    expected, checksum
+                ))
+            }
+        }
+        (None, None) => Err("[expect] needs a file or a checksum".to_string()),
+    }
+}
+
+/// Compares an output to the expected audio, sample by sample.
+///
+/// ### Parameters
+/// - `actual`: The plugin output
+/// - `expected`: The expected output
+/// - `tolerance`: The maximum difference per sample
+///
+fn compare(
+    actual: &DecodedAudio,
+    expected: &DecodedAudio,
+    tolerance: f64,
+) -> Result<String, String> {
+    if actual.channels != expected.channels || actual.sample_rate != expected.sample_rate {
+        return Err(format!(
+            "Format mismatch: expected {} ch at {} Hz, got {} ch at {} Hz",
+            expected.channels, expected.sample_rate, actual.channels, actual.sample_rate
+        ));
+    }
+    if actual.samples.len() != expected.samples.len() {
+        return Err(format!(
+            "Length mismatch: expected {} frames, got {}",
+            expected.frames(),
+            actual.frames()
+        ));
+    }
+
+    let channels = actual.channels.max(1) as usize;
+    let mut worst = (0.0f64, 0usize);
+    for (i, (a, e)) in actual.samples.iter().zip(&expected.samples).enumerate() {
+        let diff = (*a as f64 - *e as f64).abs();
+        // NaN never compares as within tolerance.
+        if diff.is_nan() || diff > worst.0 {
+            worst = (if diff.is_nan() { f64::INFINITY } else { diff }, i);
+        }
+    }
+    let (max_diff, at) = worst;
+    if max_diff > tolerance {
+        return Err(format!(
+            "Output differs by {:.3e} (tolerance {:.0e}) at frame {}, channel {}: expected {}, got {}",
+            max_diff,
+            tolerance,
+            at / channels,
+            at % channels,
+            expected.samples[at],
+            actual.samples[at]
+        ));
+    }
+    Ok(format!("max difference {:.1e}", max_diff))
+}
+
+/// Returns the SHA-256 of the output samples, as little-endian `f32`.
+fn output_checksum(audio: &DecodedAudio) -> String {
+    let mut hasher = Sha256::new();
+    for s in &audio.samples {
+        hasher.update(s.to_le_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Rewrites the `checksum` line of a test case, keeping the rest of the file.
+fn replace_checksum(text: &str, checksum: &str) -> String {
+    let mut out: Vec<String> = Vec::new();
+    let mut replaced = false;
+    for line in text.lines() {
+        let is_checksum = line
+            .split_once('=')
+            .is_some_and(|(k, _)| k.trim() == "checksum");
+        if is_checksum && !replaced {
+            out.push(format!("checksum = \"{}\"", checksum));
+            replaced = true;
+        } else {
+            out.push(line.to_string());
+        }
+    }
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+/// Warns when plugin sources changed after the archive was built, as the cases would
+/// run on stale code.
+fn warn_if_stale(plugin_dir: &Path, archive: &Path) {
+    let modified = |p: &Path| fs::metadata(p).and_then(|m| m.modified()).ok();
+    let Some(built) = modified(archive) else {
+        return;
+    };
+    let mut sources: Vec<PathBuf> = vec![
+        plugin_dir.join("plugin.toml"),
+        plugin_dir.join("Cargo.toml"),
+    ];
+    if let Ok(files) = ufs::walk_files(&plugin_dir.join("src")) {
+        sources.extend(files);
+    }
+    let newer = sources
+        .iter()
+        .filter_map(|p| modified(p))
+        .any(|m: SystemTime| m > built);
+    if newer {
+        Logger::new().log_message(
+            LogLevel::Warning,
+            &format!(
+                "Sources of {} changed since {} was built; run 'devaforge plugin build' to test them",
+                plugin_dir.to_string_lossy(),
+                archive.to_string_lossy()
+            ),
+        );
+    }
+}
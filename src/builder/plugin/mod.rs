@@ -1,7 +1,9 @@
+use crate::builder::lint::{self, AddonReport, Finding, LintFormat};
+use crate::builder::watch;
 use crate::utils::{
     fs as ufs,
     logger::{LogLevel, Logger},
-    spinner,
+    progress, spinner,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -9,7 +11,16 @@ use sha2::{Digest, Sha256};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
-use wasmparser::{ExternalKind, Parser, Payload};
+
+mod abi;
+mod bench;
+mod golden;
+mod render;
+mod runtime;
+
+pub use bench::{BenchOptions, bench_plugin};
+pub use golden::test_plugins;
+pub use render::{RenderOptions, render_plugin};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct PluginSection {
@@ -27,6 +38,12 @@ struct PluginSection {
 struct ExportEntryToml {
     name: String,
     kind: String, // func | global | memory | table
+    /// Parameter types of a function, recorded at build time
+    #[serde(default)]
+    params: Option<Vec<String>>,
+    /// Result types of a function, recorded at build time
+    #[serde(default)]
+    results: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -116,6 +133,20 @@ pub fn build_plugin(
             if *release {
                 cmd.arg("--release");
             }
+            // Concurrent builds keep cargo's output for the error instead of interleaving it.
+            if progress::in_job() {
+                let output = cmd
+                    .output()
+                    .map_err(|e| format!("Failed to run cargo build: {}", e))?;
+                if !output.status.success() {
+                    return Err(format!(
+                        "cargo build failed for plugin: exit={}\n{}",
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim_end()
+                    ));
+                }
+                return Ok(());
+            }
             let status = cmd
                 .status()
                 .map_err(|e| format!("Failed to run cargo build: {}", e))?;
@@ -186,7 +217,13 @@ pub fn build_plugin(
 
     Ok(())
 }
-pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) -> Result<(), String> {
+/// Builds all plugins under `generated/plugins`, up to `jobs` at once.
+pub fn build_all_plugins(
+    release: &bool,
+    cwd: &str,
+    require_signature: bool,
+    jobs: usize,
+) -> Result<(), String> {
     let plugins_root = Path::new(cwd).join("generated").join("plugins");
     if !plugins_root.exists() {
         return Err(format!(
@@ -202,10 +239,9 @@ pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) ->
             .and_then(|s| s.to_str())
             .map(|s| s == "plugin.toml")
             .unwrap_or(false)
+            && let Some(parent) = f.parent()
         {
-            if let Some(parent) = f.parent() {
-                dirs.push(parent.to_path_buf());
-            }
+            dirs.push(parent.to_path_buf());
         }
     }
     // Deduplicate and sort
@@ -218,11 +254,17 @@ pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) ->
     let mut errors: Vec<String> = Vec::new();
     let mut successes: Vec<String> = Vec::new();
     let total = dirs.len();
-    for p in dirs {
-        let p_str = p.to_string_lossy().to_string();
-        match build_plugin(&p_str, release, cwd, require_signature, true) {
-            Ok(_) => successes.push(p_str.clone()),
-            Err(e) => errors.push(format!("{} -> {}", p_str, e)),
+    let outcomes = progress::run_jobs(
+        &dirs,
+        jobs,
+        |p| p.to_string_lossy().to_string(),
+        |p| build_plugin(&p.to_string_lossy(), release, cwd, require_signature, true),
+    );
+    for outcome in outcomes {
+        let elapsed = outcome.elapsed.as_secs_f64();
+        match outcome.result {
+            Ok(_) => successes.push(format!("{} ({:.1}s)", outcome.label, elapsed)),
+            Err(e) => errors.push(format!("{} -> {}", outcome.label, e)),
         }
     }
 
@@ -256,6 +298,199 @@ pub fn build_all_plugins(release: &bool, cwd: &str, require_signature: bool) ->
     }
 }
 
+/// Builds plugins, then rebuilds a plugin whenever its plugin.toml, Cargo.toml or
+/// Rust sources under `src/` change, until interrupted.
+///
+/// ### Parameters
+/// - `path`: The plugin to watch, or None to watch every plugin under `generated/plugins`
+/// - `release`: Whether to build the release version
+/// - `cwd`: The current working directory
+/// - `require_signature`: Whether to fail when no signature is produced
+/// - `jobs`: The maximum number of plugins built at once on the initial build
+///
+pub fn watch_plugins(
+    path: Option<&str>,
+    release: &bool,
+    cwd: &str,
+    require_signature: bool,
+    jobs: usize,
+) -> Result<(), String> {
+    let plugins_root = Path::new(cwd).join("generated").join("plugins");
+    let only = match path {
+        Some(p) => Some(resolve_plugin_dir(cwd, p)?),
+        None => None,
+    };
+
+    // A failing initial build is reported, the fix is picked up by the watcher.
+    let initial = match &only {
+        Some(dir) => build_plugin(
+            &dir.to_string_lossy(),
+            release,
+            cwd,
+            require_signature,
+            true,
+        ),
+        None => build_all_plugins(release, cwd, require_signature, jobs),
+    };
+    if let Err(e) = initial {
+        Logger::new().log_message(LogLevel::Error, &e);
+    }
+
+    watch::watch_addons(
+        &plugins_root,
+        "plugin.toml",
+        only.as_deref(),
+        |rel| {
+            rel == Path::new("plugin.toml")
+                || rel == Path::new("Cargo.toml")
+                || (rel.starts_with("src") && rel.extension().is_some_and(|e| e == "rs"))
+        },
+        |dir| {
+            build_plugin(
+                &dir.to_string_lossy(),
+                release,
+                cwd,
+                require_signature,
+                true,
+            )
+        },
+    )
+}
+
+/// Checks plugins without building them and prints the problems found.
+///
+/// Fails when any plugin has an error, so it can gate merges; warnings alone pass.
+///
+/// ### Parameters
+/// - `path`: The plugin to lint, or None to lint every plugin under `generated/plugins`
+/// - `cwd`: The current working directory
+/// - `format`: The output format: `human` or `json`
+///
+pub fn lint_plugins(path: Option<&str>, cwd: &str, format: &str) -> Result<(), String> {
+    let format = LintFormat::parse(format)?;
+    let plugins_root = Path::new(cwd).join("generated").join("plugins");
+    let only = match path {
+        Some(p) => Some(resolve_plugin_dir(cwd, p)?),
+        None => None,
+    };
+    let reports: Vec<AddonReport> = lint::addon_dirs(&plugins_root, "plugin.toml", only)?
+        .iter()
+        .map(|dir| lint_plugin(dir, &plugins_root))
+        .collect();
+    lint::print_reports(&reports, format)
+}
+
+/// Runs every check on one plugin.
+///
+/// ### Parameters
+/// - `plugin_dir`: The plugin directory
+/// - `plugins_root`: The `generated/plugins` directory, to shorten the reported path
+///
+fn lint_plugin(plugin_dir: &Path, plugins_root: &Path) -> AddonReport {
+    let mut report = AddonReport {
+        kind: "plugin",
+        id: ufs::path_relative_to(plugin_dir, plugins_root)
+            .map(ufs::to_unix_string)
+            .unwrap_or_else(|| plugin_dir.to_string_lossy().to_string()),
+        path: plugin_dir.to_string_lossy().to_string(),
+        findings: Vec::new(),
+    };
+
+    let doc: PluginTomlDoc = match fs::read_to_string(plugin_dir.join("plugin.toml"))
+        .map_err(|e| e.to_string())
+        .and_then(|txt| toml::from_str(&txt).map_err(|e| e.to_string()))
+    {
+        Ok(doc) => doc,
+        Err(e) => {
+            report.findings.push(Finding::error(
+                "invalid-manifest",
+                Some("plugin.toml".to_string()),
+                format!("Cannot read plugin.toml: {}", e),
+            ));
+            return report;
+        }
+    };
+    let plugin = &doc.plugin;
+    if !plugin.publisher.is_empty() && !plugin.name.is_empty() {
+        report.id = format!("{}.{}", plugin.publisher, plugin.name);
+    }
+    report.findings.extend(lint::check_manifest(
+        "plugin",
+        &plugin.publisher,
+        &plugin.name,
+        plugin.version.as_deref(),
+        plugin.access.as_deref(),
+    ));
+    for export in &doc.exports {
+        if !["func", "global", "memory", "table"].contains(&export.kind.as_str()) {
+            report.findings.push(Finding::error(
+                "invalid-export-kind",
+                Some("plugin.toml".to_string()),
+                format!(
+                    "Export '{}' has unknown kind '{}' (expected: func | global | memory | table)",
+                    export.name, export.kind
+                ),
+            ));
+        }
+        if export.kind == "func"
+            && let Some(params) = &export.params
+        {
+            let signature = abi::FuncSignature {
+                params: params.clone(),
+                results: export.results.clone().unwrap_or_default(),
+            };
+            if let Err(e) = abi::check_signature(&export.name, &signature) {
+                report.findings.push(Finding::error(
+                    "invalid-export-signature",
+                    Some("plugin.toml".to_string()),
+                    e,
+                ));
+            }
+        }
+    }
+    if !plugin_dir.join("Cargo.toml").is_file() {
+        report.findings.push(Finding::error(
+            "missing-cargo-toml",
+            None,
+            "Cargo.toml is missing; the plugin cannot be compiled".to_string(),
+        ));
+    }
+    if let Some(wasm_path) = built_wasm(plugin_dir, &plugin.name)
+        && let Ok(wasm) = fs::read(&wasm_path)
+    {
+        let wasm_name = ufs::path_relative_to(&wasm_path, plugin_dir)
+            .map(ufs::to_unix_string)
+            .unwrap_or_else(|| wasm_path.to_string_lossy().to_string());
+        report.findings.extend(abi::check_module(&wasm, &wasm_name));
+    }
+    report.findings.extend(lint::check_docs(plugin_dir));
+    report
+}
+
+/// Returns the most recent WASM artifact compiled for a plugin, release or debug.
+///
+/// ### Parameters
+/// - `plugin_dir`: The plugin directory
+/// - `name`: The plugin name
+///
+fn built_wasm(plugin_dir: &Path, name: &str) -> Option<PathBuf> {
+    ["release", "debug"]
+        .iter()
+        .map(|profile| {
+            plugin_dir
+                .join("target")
+                .join("wasm32-unknown-unknown")
+                .join(profile)
+                .join(format!("{}.wasm", name))
+        })
+        .filter_map(|p| {
+            let modified = fs::metadata(&p).and_then(|m| m.modified()).ok()?;
+            Some((modified, p))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, p)| p)
+}
+
 fn resolve_plugin_dir(cwd: &str, input: &str) -> Result<PathBuf, String> {
     let candidate = Path::new(cwd).join(input);
     if candidate.is_file()
@@ -470,6 +705,7 @@ fn create_plugin_tar_gz_wasm_only(
     fs::File::open(&wasm_path)
         .and_then(|mut f| f.read_to_end(&mut wasm_bytes))
         .map_err(|e| format!("Failed to read wasm: {}", e))?;
+    abi::validate_module(&wasm_bytes, &format!("{}.wasm", name))?;
 
     // Read and parse original plugin.toml to preserve [plugin] metadata.
     let toml_txt = fs::read_to_string(plugin_toml_path)
@@ -480,60 +716,59 @@ fn create_plugin_tar_gz_wasm_only(
     // Scan plugin sources for #[export_plugin("name")] annotations and collect names.
     let mut attribute_exports: Vec<String> = Vec::new();
     let src_root = plugin_dir.join("src");
-    if src_root.exists() {
-        if let Ok(files) = ufs::walk_files(&src_root) {
-            for f in files {
-                if !f.is_file() {
-                    continue;
-                }
-                if let Some(ext) = f.extension().and_then(|s| s.to_str()) {
-                    if ext != "rs" {
-                        continue;
-                    }
-                } else {
+    if src_root.exists()
+        && let Ok(files) = ufs::walk_files(&src_root)
+    {
+        for f in files {
+            if !f.is_file() {
+                continue;
+            }
+            if let Some(ext) = f.extension().and_then(|s| s.to_str()) {
+                if ext != "rs" {
                     continue;
                 }
-                if let Ok(s) = fs::read_to_string(&f) {
-                    let mut pos = 0usize;
-                    while let Some(idx) = s[pos..].find("export_plugin(") {
-                        let start = pos + idx;
-                        // find opening quote
-                        if let Some(q) = s[start..].find('"') {
-                            let qpos = start + q + 1;
-                            if let Some(endq) = s[qpos..].find('"') {
-                                let name = &s[qpos..qpos + endq];
-                                attribute_exports.push(name.to_string());
-                                pos = qpos + endq + 1;
-                                continue;
-                            }
+            } else {
+                continue;
+            }
+            if let Ok(s) = fs::read_to_string(&f) {
+                let mut pos = 0usize;
+                while let Some(idx) = s[pos..].find("export_plugin(") {
+                    let start = pos + idx;
+                    // find opening quote
+                    if let Some(q) = s[start..].find('"') {
+                        let qpos = start + q + 1;
+                        if let Some(endq) = s[qpos..].find('"') {
+                            let name = &s[qpos..qpos + endq];
+                            attribute_exports.push(name.to_string());
+                            pos = qpos + endq + 1;
+                            continue;
                         }
-                        break;
                     }
+                    break;
                 }
             }
         }
     }
 
-    // Parse wasm exports and collect relevant exported function names
-    let mut exported_funcs: Vec<String> = Vec::new();
-    for payload in Parser::new(0).parse_all(&wasm_bytes).flatten() {
-        if let Payload::ExportSection(reader) = payload {
-            for exp in reader.into_iter().flatten() {
-                if exp.kind == ExternalKind::Func {
-                    let name = exp.name.to_string();
-                    // include setters and any names declared via attribute
-                    if name.starts_with("set_") || attribute_exports.iter().any(|a| a == &name) {
-                        exported_funcs.push(name);
-                    }
-                }
-            }
-        }
+    // Read exported function types and keep setters and any names declared via attribute
+    let signatures = abi::export_signatures(&wasm_bytes)?;
+    let exported_funcs: Vec<(&String, &abi::FuncSignature)> = signatures
+        .iter()
+        .filter(|(name, _)| name.starts_with("set_") || attribute_exports.contains(name))
+        .collect();
+
+    // Fail on exports the host would call with the wrong arguments
+    let mismatches: Vec<String> = exported_funcs
+        .iter()
+        .filter_map(|(name, signature)| abi::check_signature(name, signature).err())
+        .collect();
+    if !mismatches.is_empty() {
+        return Err(format!(
+            "Exported functions do not match the Devalang plugin ABI:\n - {}",
+            mismatches.join("\n - ")
+        ));
     }
 
-    // Deduplicate and sort for stable output
-    exported_funcs.sort();
-    exported_funcs.dedup();
-
     // Rebuild plugin.toml content: keep [plugin] section and replace exports with the detected ones
     let mut out_toml = String::new();
     if let Some(doc) = plugin_doc {
@@ -559,9 +794,18 @@ fn create_plugin_tar_gz_wasm_only(
         }
     }
 
-    for name in exported_funcs {
+    let toml_types = |types: &[String]| {
+        let quoted: Vec<String> = types.iter().map(|t| format!("\"{}\"", t)).collect();
+        format!("[{}]", quoted.join(", "))
+    };
+    for (name, signature) in exported_funcs {
         out_toml.push_str("\n[[exports]]\n");
         out_toml.push_str(&format!("name = \"{}\"\nkind = \"func\"\n", name));
+        out_toml.push_str(&format!(
+            "params = {}\nresults = {}\n",
+            toml_types(&signature.params),
+            toml_types(&signature.results)
+        ));
     }
 
     // Overwrite the source plugin.toml so generated/plugins/<publisher>/<name>/plugin.toml
@@ -0,0 +1,178 @@
+use super::runtime::{self, BufferFunction, PluginRuntime};
+use crate::audio::{
+    decode::{self, DecodedAudio},
+    encode, probe, signal,
+};
+use crate::utils::logger::{LogLevel, Logger};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings of a plugin render.
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Exported function to run; may be omitted when the plugin exports a single one
+    pub function: Option<String>,
+    /// Values of the function arguments following the buffer
+    pub args: Vec<f64>,
+    /// Audio file to process instead of a generated signal
+    pub input: Option<String>,
+    /// Generated signal: sine | sweep | noise | impulse
+    pub signal: String,
+    /// Frequency of the generated signal in Hz (end frequency of a sweep)
+    pub freq: f64,
+    /// Duration of the generated signal in seconds
+    pub duration: f64,
+    /// Sample rate of the generated signal in Hz
+    pub sample_rate: u32,
+    /// Output WAV file; defaults to `output/render/<publisher>.<name>.<function>.wav`
+    pub out: Option<String>,
+}
+
+/// Runs a built plugin on a test signal or an audio file and writes the result to a WAV file.
+///
+/// The plugin is loaded from its archive in `output/plugin/` into an embedded
+/// WebAssembly interpreter, so no Devalang install is needed. Every channel is
+/// processed as its own mono buffer, in a single call.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `id`: The plugin identifier: `<publisher>.<name>`
+/// - `options`: The render settings
+///
+pub fn render_plugin(cwd: &str, id: &str, options: &RenderOptions) -> Result<(), String> {
+    let archive = runtime::archive_path(cwd, id)?;
+    let wasm = runtime::read_archive_wasm(&archive)?;
+    let mut plugin = PluginRuntime::load(&wasm)?;
+    let function = select_function(&plugin.buffer_functions(), options.function.as_deref())?;
+
+    let (mut audio, source) = match &options.input {
+        Some(input) => {
+            let path = Path::new(cwd).join(input);
+            (decode::decode_file(&path)?, input.clone())
+        }
+        None => (
+            signal::generate(
+                &options.signal,
+                options.freq,
+                options.duration,
+                options.sample_rate,
+            )?,
+            format!(
+                "{} {} Hz, {} s at {} Hz",
+                options.signal, options.freq, options.duration, options.sample_rate
+            ),
+        ),
+    };
+    let input_peak = probe::peak_db(&audio.samples);
+    process_channels(&mut plugin, &function, &mut audio, &options.args)?;
+
+    let out_path = match &options.out {
+        Some(out) => Path::new(cwd).join(out),
+        None => default_out_path(cwd, id, &function.name),
+    };
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.to_string_lossy(), e))?;
+    }
+    encode::write_wav(&audio, 32, &out_path)?;
+
+    let args: Vec<String> = options.args.iter().map(|a| a.to_string()).collect();
+    Logger::new().log_message_with_trace(
+        LogLevel::Success,
+        &format!(
+            "Rendered {} to {}",
+            function.name,
+            out_path.to_string_lossy()
+        ),
+        vec![
+            &format!("Input: {}", source),
+            &format!("Arguments: ({})", args.join(", ")),
+            &format!(
+                "Peak: {:.2} dBFS in, {:.2} dBFS out",
+                input_peak,
+                probe::peak_db(&audio.samples)
+            ),
+        ],
+    );
+    Ok(())
+}
+
+/// Picks the buffer function to run.
+///
+/// ### Parameters
+/// - `functions`: The buffer functions exported by the plugin
+/// - `requested`: The function asked for, if any
+///
+pub(super) fn select_function(
+    functions: &[BufferFunction],
+    requested: Option<&str>,
+) -> Result<BufferFunction, String> {
+    let names: Vec<&str> = functions.iter().map(|f| f.name.as_str()).collect();
+    match requested {
+        Some(name) => functions
+            .iter()
+            .find(|f| f.name == name)
+            .cloned()
+            .ok_or_else(|| {
+                format!(
+                    "'{}' is not an exported buffer function (available: {})",
+                    name,
+                    if names.is_empty() {
+                        "none".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                )
+            }),
+        None => match functions {
+            [only] => Ok(only.clone()),
+            [] => Err(
+                "The plugin exports no buffer function (ptr: i32, len: i32, ...args)".to_string(),
+            ),
+            _ => Err(format!(
+                "The plugin exports several buffer functions ({}); pick one with --function",
+                names.join(", ")
+            )),
+        },
+    }
+}
+
+/// Runs a buffer function on every channel of some audio, in place.
+///
+/// ### Parameters
+/// - `plugin`: The plugin
+/// - `function`: The buffer function
+/// - `audio`: The audio to process
+/// - `args`: The values of the arguments following the buffer
+///
+pub(super) fn process_channels(
+    plugin: &mut PluginRuntime,
+    function: &BufferFunction,
+    audio: &mut DecodedAudio,
+    args: &[f64],
+) -> Result<(), String> {
+    let channels = audio.channels.max(1) as usize;
+    for c in 0..channels {
+        let mut buffer: Vec<f32> = audio
+            .samples
+            .iter()
+            .skip(c)
+            .step_by(channels)
+            .copied()
+            .collect();
+        plugin.process(function, &mut buffer, args)?;
+        for (i, s) in buffer.into_iter().enumerate() {
+            audio.samples[i * channels + c] = s;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `output/render/<publisher>.<name>.<function>.wav`.
+fn default_out_path(cwd: &str, id: &str, function: &str) -> PathBuf {
+    let id = id.strip_prefix("plugin.").unwrap_or(id);
+    Path::new(cwd)
+        .join("output")
+        .join("render")
+        .join(format!("{}.{}.wav", id, function))
+}
@@ -0,0 +1,300 @@
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tar::Archive;
+use wasmi::core::{Pages, ValType};
+use wasmi::{Engine, Extern, Instance, Linker, Memory, Module, Store, Val};
+
+/// Size of a WebAssembly memory page.
+const PAGE_SIZE: usize = 65536;
+
+/// An exported function processing an audio buffer in place: `(ptr, len, args...)`.
+#[derive(Debug, Clone)]
+pub(super) struct BufferFunction {
+    pub name: String,
+    /// Types of the arguments following `ptr` and `len`
+    pub args: Vec<ValType>,
+}
+
+/// A plugin instantiated in an embedded WebAssembly interpreter.
+pub(super) struct PluginRuntime {
+    store: Store<()>,
+    instance: Instance,
+    memory: Memory,
+    /// Region of the plugin memory reserved for the audio buffer: offset and size in bytes
+    scratch: Option<(usize, usize)>,
+}
+
+impl PluginRuntime {
+    /// Compiles and instantiates a plugin module.
+    ///
+    /// The module must export its linear memory as `memory` and must not import
+    /// anything: plugins run without a host environment.
+    ///
+    /// ### Parameters
+    /// - `wasm`: The module bytes
+    ///
+    pub(super) fn load(wasm: &[u8]) -> Result<Self, String> {
+        let engine = Engine::default();
+        let module =
+            Module::new(&engine, wasm).map_err(|e| format!("Invalid WASM module: {}", e))?;
+
+        let imports: Vec<String> = module
+            .imports()
+            .map(|i| format!("{}::{}", i.module(), i.name()))
+            .collect();
+        if !imports.is_empty() {
+            return Err(format!(
+                "The plugin imports host items that are not available offline: {}",
+                imports.join(", ")
+            ));
+        }
+
+        let mut store = Store::new(&engine, ());
+        let linker = Linker::<()>::new(&engine);
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Failed to instantiate the plugin: {}", e))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| "The plugin does not export its memory as 'memory'".to_string())?;
+
+        Ok(PluginRuntime {
+            store,
+            instance,
+            memory,
+            scratch: None,
+        })
+    }
+
+    /// Returns the exported functions taking an audio buffer, sorted by name.
+    ///
+    /// A buffer function takes a pointer and a length (`i32`) followed by numeric
+    /// arguments, and returns nothing.
+    pub(super) fn buffer_functions(&self) -> Vec<BufferFunction> {
+        let mut out: Vec<BufferFunction> = self
+            .instance
+            .exports(&self.store)
+            .filter_map(|export| {
+                let name = export.name().to_string();
+                let Extern::Func(func) = export.into_extern() else {
+                    return None;
+                };
+                let ty = func.ty(&self.store);
+                match ty.params() {
+                    [ValType::I32, ValType::I32, args @ ..]
+                        if ty.results().is_empty()
+                            && args.iter().all(|t| {
+                                matches!(
+                                    t,
+                                    ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64
+                                )
+                            }) =>
+                    {
+                        Some(BufferFunction {
+                            name,
+                            args: args.to_vec(),
+                        })
+                    }
+                    _ => None,
+                }
+            })
+            .collect();
+        out.sort_by(|a, b| a.name.cmp(&b.name));
+        out
+    }
+
+    /// Runs a buffer function on `buffer`, in place.
+    ///
+    /// The buffer is copied into memory grown past the plugin's own data, so the
+    /// plugin allocator never hands it out, then copied back after the call.
+    ///
+    /// ### Parameters
+    /// - `function`: The buffer function
+    /// - `buffer`: The samples to process
+    /// - `args`: The values of the arguments following `ptr` and `len`
+    ///
+    pub(super) fn process(
+        &mut self,
+        function: &BufferFunction,
+        buffer: &mut [f32],
+        args: &[f64],
+    ) -> Result<(), String> {
+        check_arg_count(function, args)?;
+        let func = self
+            .instance
+            .get_func(&self.store, &function.name)
+            .ok_or_else(|| format!("Function not exported: {}", function.name))?;
+
+        let offset = self.write_buffer(buffer)?;
+        let params = call_params(function, offset, buffer.len(), args);
+        func.call(&mut self.store, &params, &mut [])
+            .map_err(|e| format!("{} failed: {}", function.name, e))?;
+
+        let mut out = vec![0u8; std::mem::size_of_val(buffer)];
+        self.memory
+            .read(&self.store, offset, &mut out)
+            .map_err(|e| format!("Failed to read the buffer: {}", e))?;
+        for (s, chunk) in buffer.iter_mut().zip(out.chunks_exact(4)) {
+            *s = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        Ok(())
+    }
+
+    /// Times `iterations` calls of a buffer function on a copy of `buffer`.
+    ///
+    /// The buffer is written back before every call so each one processes the same
+    /// input; only the calls themselves are timed.
+    ///
+    /// ### Parameters
+    /// - `function`: The buffer function
+    /// - `buffer`: The samples to process
+    /// - `args`: The values of the arguments following `ptr` and `len`
+    /// - `iterations`: The number of calls
+    ///
+    pub(super) fn time_calls(
+        &mut self,
+        function: &BufferFunction,
+        buffer: &[f32],
+        args: &[f64],
+        iterations: usize,
+    ) -> Result<Vec<Duration>, String> {
+        check_arg_count(function, args)?;
+        let func = self
+            .instance
+            .get_func(&self.store, &function.name)
+            .ok_or_else(|| format!("Function not exported: {}", function.name))?;
+
+        let mut times = Vec::with_capacity(iterations);
+        for _ in 0..iterations {
+            let offset = self.write_buffer(buffer)?;
+            let params = call_params(function, offset, buffer.len(), args);
+            let start = Instant::now();
+            func.call(&mut self.store, &params, &mut [])
+                .map_err(|e| format!("{} failed: {}", function.name, e))?;
+            times.push(start.elapsed());
+        }
+        Ok(times)
+    }
+
+    /// Returns the current size of the plugin memory in bytes.
+    pub(super) fn memory_size(&self) -> usize {
+        u32::from(self.memory.current_pages(&self.store)) as usize * PAGE_SIZE
+    }
+
+    /// Copies a buffer into the reserved memory region and returns its offset.
+    fn write_buffer(&mut self, buffer: &[f32]) -> Result<usize, String> {
+        let offset = self.reserve(std::mem::size_of_val(buffer))?;
+        let bytes: Vec<u8> = buffer.iter().flat_map(|s| s.to_le_bytes()).collect();
+        self.memory
+            .write(&mut self.store, offset, &bytes)
+            .map_err(|e| format!("Failed to write the buffer: {}", e))?;
+        Ok(offset)
+    }
+
+    /// Returns the offset of a memory region of at least `size` bytes reserved for buffers.
+    fn reserve(&mut self, size: usize) -> Result<usize, String> {
+        if let Some((offset, capacity)) = self.scratch
+            && capacity >= size
+        {
+            return Ok(offset);
+        }
+        let pages = size.div_ceil(PAGE_SIZE).max(1);
+        let grown = Pages::new(pages as u32)
+            .and_then(|p| self.memory.grow(&mut self.store, p).ok())
+            .ok_or_else(|| format!("The plugin memory cannot grow by {} bytes", size))?;
+        let offset = u32::from(grown) as usize * PAGE_SIZE;
+        self.scratch = Some((offset, pages * PAGE_SIZE));
+        Ok(offset)
+    }
+}
+
+/// Fails unless `args` has one value per argument following the buffer.
+fn check_arg_count(function: &BufferFunction, args: &[f64]) -> Result<(), String> {
+    if args.len() != function.args.len() {
+        return Err(format!(
+            "{} takes {} argument(s) after the buffer, got {}",
+            function.name,
+            function.args.len(),
+            args.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Builds the call parameters of a buffer function: `ptr`, `len`, then `args` cast to
+/// the declared types.
+fn call_params(function: &BufferFunction, offset: usize, len: usize, args: &[f64]) -> Vec<Val> {
+    let mut params = vec![Val::I32(offset as i32), Val::I32(len as i32)];
+    for (ty, value) in function.args.iter().zip(args) {
+        params.push(match ty {
+            ValType::I32 => Val::I32(*value as i32),
+            ValType::I64 => Val::I64(*value as i64),
+            ValType::F32 => Val::F32((*value as f32).into()),
+            _ => Val::F64((*value).into()),
+        });
+    }
+    params
+}
+
+/// Returns the path of the archive built for a plugin, `output/plugin/<publisher>.<name>.tar.gz`.
+///
+/// ### Parameters
+/// - `cwd`: The current working directory
+/// - `id`: The plugin identifier: `<publisher>.<name>` or `plugin.<publisher>.<name>`
+///
+pub(super) fn archive_path(cwd: &str, id: &str) -> Result<PathBuf, String> {
+    let id = id.strip_prefix("plugin.").unwrap_or(id);
+    if id.split('.').count() != 2 || id.split('.').any(str::is_empty) {
+        return Err(format!(
+            "Invalid plugin id '{}': expected <publisher>.<name>",
+            id
+        ));
+    }
+    let path = Path::new(cwd)
+        .join("output")
+        .join("plugin")
+        .join(format!("{}.tar.gz", id));
+    if !path.is_file() {
+        return Err(format!(
+            "Plugin archive not found: {}; run 'devaforge plugin build' first",
+            path.to_string_lossy()
+        ));
+    }
+    Ok(path)
+}
+
+/// Reads the `.wasm` module packed in a plugin archive.
+///
+/// ### Parameters
+/// - `archive_path`: The plugin archive
+///
+pub(super) fn read_archive_wasm(archive_path: &Path) -> Result<Vec<u8>, String> {
+    let file = fs::File::open(archive_path)
+        .map_err(|e| format!("Failed to open {}: {}", archive_path.to_string_lossy(), e))?;
+    let mut archive = Archive::new(GzDecoder::new(file));
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read {}: {}", archive_path.to_string_lossy(), e))?;
+    for entry in entries {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let is_wasm = entry
+            .path()
+            .map(|p| p.extension().is_some_and(|e| e == "wasm"))
+            .unwrap_or(false);
+        if is_wasm {
+            let mut bytes = Vec::new();
+            entry
+                .read_to_end(&mut bytes)
+                .map_err(|e| format!("Failed to read the WASM module: {}", e))?;
+            return Ok(bytes);
+        }
+    }
+    Err(format!(
+        "No .wasm module in {}",
+        archive_path.to_string_lossy()
+    ))
+}
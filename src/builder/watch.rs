@@ -0,0 +1,127 @@
+use crate::utils::logger::{LogLevel, Logger};
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{DebouncedEventKind, new_debouncer};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Quiet period after the last change before an addon is rebuilt.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches a directory of addons and rebuilds the addon a change belongs to.
+///
+/// A change belongs to the closest parent directory holding `manifest`, and only
+/// triggers a rebuild when `is_source` accepts its path relative to that directory.
+/// Rewrites of the manifest by the build itself are ignored. Runs until the process
+/// is interrupted; build errors are logged and watching goes on.
+///
+/// ### Parameters
+/// - `root`: The directory holding the addons (`generated/banks`, `generated/plugins`)
+/// - `manifest`: The manifest file name of an addon (`bank.toml`, `plugin.toml`)
+/// - `only`: Restricts rebuilds to a single addon directory
+/// - `is_source`: Returns true for relative paths the addon is built from
+/// - `rebuild`: Builds the addon at the given directory
+///
+pub fn watch_addons<S, B>(
+    root: &Path,
+    manifest: &str,
+    only: Option<&Path>,
+    is_source: S,
+    mut rebuild: B,
+) -> Result<(), String>
+where
+    S: Fn(&Path) -> bool,
+    B: FnMut(&Path) -> Result<(), String>,
+{
+    let (tx, rx) = mpsc::channel();
+    let mut debouncer =
+        new_debouncer(DEBOUNCE, tx).map_err(|e| format!("Failed to start watcher: {}", e))?;
+    debouncer
+        .watcher()
+        .watch(root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root.to_string_lossy(), e))?;
+
+    let logger = Logger::new();
+    logger.log_message(
+        LogLevel::Watcher,
+        &format!(
+            "Watching {} for changes (Ctrl+C to stop)",
+            only.unwrap_or(root).to_string_lossy()
+        ),
+    );
+
+    // Manifest content left by the last build of each addon.
+    let mut built_manifests: HashMap<PathBuf, Vec<u8>> = HashMap::new();
+    let canonical_only = only.and_then(|p| fs::canonicalize(p).ok());
+
+    for result in rx {
+        let events = match result {
+            Ok(events) => events,
+            Err(e) => {
+                logger.log_message(LogLevel::Warning, &format!("Watcher error: {}", e));
+                continue;
+            }
+        };
+
+        let mut changed: BTreeSet<PathBuf> = BTreeSet::new();
+        for event in events {
+            // Files still being written are picked up by their final event.
+            if event.kind != DebouncedEventKind::Any {
+                continue;
+            }
+            let Some(addon_dir) = addon_dir_of(&event.path, root, manifest) else {
+                continue;
+            };
+            if let Some(only) = &canonical_only
+                && fs::canonicalize(&addon_dir).ok().as_ref() != Some(only)
+            {
+                continue;
+            }
+            let Ok(rel) = event.path.strip_prefix(&addon_dir) else {
+                continue;
+            };
+            if !is_source(rel) {
+                continue;
+            }
+            if rel == Path::new(manifest)
+                && let Some(built) = built_manifests.get(&addon_dir)
+                && fs::read(&event.path).ok().as_ref() == Some(built)
+            {
+                continue;
+            }
+            changed.insert(addon_dir);
+        }
+
+        for addon_dir in changed {
+            logger.log_message(
+                LogLevel::Watcher,
+                &format!("Change detected in {}", addon_dir.to_string_lossy()),
+            );
+            if let Err(e) = rebuild(&addon_dir) {
+                logger.log_message(LogLevel::Error, &e);
+            }
+            if let Ok(content) = fs::read(addon_dir.join(manifest)) {
+                built_manifests.insert(addon_dir, content);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the closest directory holding `manifest` among the parents of a path,
+/// without leaving `root`.
+fn addon_dir_of(path: &Path, root: &Path, manifest: &str) -> Option<PathBuf> {
+    let mut dir = path.parent();
+    while let Some(d) = dir {
+        if !d.starts_with(root) {
+            return None;
+        }
+        if d.join(manifest).is_file() {
+            return Some(d.to_path_buf());
+        }
+        dir = d.parent();
+    }
+    None
+}
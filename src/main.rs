@@ -9,6 +9,7 @@ use std::env;
 use tokio::io;
 
 mod addon;
+mod audio;
 mod builder;
 mod publisher;
 mod types;
@@ -43,6 +44,15 @@ enum Commands {
     /// Update an existing addon in the official Devalang repository
     Update {},
 
+    /// Install a built bank or plugin into a Devalang project
+    Install {
+        /// Addon identifier: <publisher>.<name> (or bank.<publisher>.<name>, plugin.<publisher>.<name>)
+        id: String,
+        #[arg(long)]
+        /// Project directory (defaults to the project containing the current directory)
+        project: Option<String>,
+    },
+
     /// Manage Publishers
     Publisher {
         #[command(subcommand)]
@@ -59,6 +69,24 @@ enum BankCommands {
     Build {
         /// Relative path OR alias bank.<bankId>. Leave empty to build all.
         path: Option<String>,
+        #[arg(short, long, default_value_t = false)]
+        /// Rebuild banks even if nothing changed since their last build
+        force: bool,
+        #[arg(short, long, default_value_t = 1)]
+        /// Number of banks built at once when building all (0 = one per CPU)
+        jobs: usize,
+        #[arg(short, long, default_value_t = false)]
+        /// Keep running and rebuild a bank whenever its files change
+        watch: bool,
+    },
+
+    /// Check banks for problems without building them
+    Lint {
+        /// Relative path OR alias bank.<bankId>. Leave empty to lint all.
+        path: Option<String>,
+        #[arg(long, default_value = "human")]
+        /// Output format: human | json
+        format: String,
     },
 
     /// List available banks
@@ -77,6 +105,33 @@ enum BankCommands {
         /// Bank identifier: <publisher>.<name>
         id: String,
     },
+
+    /// Import an SFZ, DecentSampler or SoundFont instrument as a new bank
+    Import {
+        /// Instrument file: .sfz | .dspreset | .sf2
+        file: String,
+        #[arg(long)]
+        /// Bank publisher
+        publisher: String,
+        #[arg(long)]
+        /// Bank name (defaults to the instrument file name)
+        name: Option<String>,
+        #[arg(long)]
+        /// Bank description
+        description: Option<String>,
+    },
+
+    /// Export a bank to another instrument format
+    Export {
+        /// Bank identifier: <publisher>.<name>
+        id: String,
+        #[arg(long, default_value = "sfz")]
+        /// Export format: sfz
+        format: String,
+        #[arg(long)]
+        /// Output folder (defaults to output/<format>/<publisher>.<name>)
+        out: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -94,6 +149,79 @@ enum PluginCommands {
         #[arg(long, default_value_t = false)]
         /// Require artifact to be signed (will error if no signature produced)
         require_signature: bool,
+        #[arg(short, long, default_value_t = 1)]
+        /// Number of plugins built at once when building all (0 = one per CPU)
+        jobs: usize,
+        #[arg(short, long, default_value_t = false)]
+        /// Keep running and rebuild a plugin whenever its sources change
+        watch: bool,
+    },
+
+    /// Check plugins for problems without building them
+    Lint {
+        /// Relative path OR alias plugin.<pluginId>. Leave empty to lint all.
+        path: Option<String>,
+        #[arg(long, default_value = "human")]
+        /// Output format: human | json
+        format: String,
+    },
+
+    /// Run a built plugin on a test signal or an audio file and write the result to a WAV file
+    Render {
+        /// Plugin identifier: <publisher>.<name>
+        id: String,
+        #[arg(long)]
+        /// Exported function to run (required if the plugin exports several)
+        function: Option<String>,
+        #[arg(long = "arg", allow_hyphen_values = true)]
+        /// Value of the next function argument after the buffer (repeat for each argument)
+        args: Vec<f64>,
+        #[arg(long)]
+        /// Audio file to process instead of a generated signal
+        input: Option<String>,
+        #[arg(long, default_value = "sine")]
+        /// Generated signal: sine | sweep | noise | impulse
+        signal: String,
+        #[arg(long, default_value_t = 440.0)]
+        /// Frequency of the generated signal in Hz (end frequency of a sweep)
+        freq: f64,
+        #[arg(long, default_value_t = 2.0)]
+        /// Duration of the generated signal in seconds
+        duration: f64,
+        #[arg(long, default_value_t = 44100)]
+        /// Sample rate of the generated signal in Hz
+        sample_rate: u32,
+        #[arg(long)]
+        /// Output WAV file (defaults to output/render/<publisher>.<name>.<function>.wav)
+        out: Option<String>,
+    },
+
+    /// Benchmark the exported functions of a built plugin against the real-time budget
+    Bench {
+        /// Plugin identifier: <publisher>.<name>
+        id: String,
+        #[arg(long)]
+        /// Exported function to benchmark (defaults to all of them)
+        function: Option<String>,
+        #[arg(long = "arg", allow_hyphen_values = true)]
+        /// Value of the next function argument after the buffer (each defaults to 1)
+        args: Vec<f64>,
+        #[arg(long, default_value_t = 200)]
+        /// Timed calls per buffer size
+        iterations: usize,
+        #[arg(long)]
+        /// Fail when a function exceeds this load, in percent of the real-time budget
+        /// as measured in the interpreter (only reported when omitted)
+        max_load: Option<f64>,
+    },
+
+    /// Run the golden-audio test cases in the tests/ folder of plugins
+    Test {
+        /// Relative path OR alias plugin.<pluginId>. Leave empty to test all.
+        path: Option<String>,
+        #[arg(long, default_value_t = false)]
+        /// Record the current output as the expected one instead of comparing
+        update: bool,
     },
 
     /// List available plugins
@@ -132,7 +260,7 @@ async fn main() -> io::Result<()> {
     cmd = cmd.version(version_static).before_help(signature_static);
 
     let raw_args: Vec<String> = std::env::args().collect();
-    if raw_args.iter().any(|a| (a == "--version" || a == "-V")) {
+    if raw_args.iter().any(|a| a == "--version" || a == "-V") {
         println!("{}", signature_static);
         return Ok(());
     }
@@ -163,6 +291,14 @@ async fn main() -> io::Result<()> {
             Ok(())
         }
 
+        Commands::Install { id, project } => {
+            if let Err(e) = addon::install::install_addon(&cwd, &id, project.as_deref()) {
+                return Err(io::Error::other(e));
+            }
+
+            Ok(())
+        }
+
         Commands::Bank { command } => match command {
             BankCommands::Create {} => {
                 if let Err(e) = addon::bank::prompt::prompt_bank_addon(&cwd).await {
@@ -172,13 +308,27 @@ async fn main() -> io::Result<()> {
                 Ok(())
             }
 
-            BankCommands::Build { path } => {
+            BankCommands::Build {
+                path,
+                force,
+                jobs,
+                watch,
+            } => {
+                if watch {
+                    let res = tokio::task::spawn_blocking(move || {
+                        bank_builder::watch_banks(path.as_deref(), &cwd, force, resolve_jobs(jobs))
+                    })
+                    .await
+                    .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                    return res.map_err(io::Error::other);
+                }
+
                 match path {
                     Some(p) => {
                         let cwd_clone = cwd.clone();
                         let p_clone = p.clone();
                         let res = tokio::task::spawn_blocking(move || {
-                            bank_builder::build_bank(&p_clone, &cwd_clone)
+                            bank_builder::build_bank(&p_clone, &cwd_clone, force)
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -189,7 +339,7 @@ async fn main() -> io::Result<()> {
                     None => {
                         let cwd_clone = cwd.clone();
                         let res = tokio::task::spawn_blocking(move || {
-                            bank_builder::build_all_banks(&cwd_clone)
+                            bank_builder::build_all_banks(&cwd_clone, force, resolve_jobs(jobs))
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -202,6 +352,19 @@ async fn main() -> io::Result<()> {
                 Ok(())
             }
 
+            BankCommands::Lint { path, format } => {
+                let res = tokio::task::spawn_blocking(move || {
+                    bank_builder::lint_banks(path.as_deref(), &cwd, &format)
+                })
+                .await
+                .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                if let Err(e) = res {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
+
             BankCommands::List {} => {
                 if let Err(e) = addon::bank::manage::list_banks(&cwd) {
                     return Err(io::Error::other(e));
@@ -225,6 +388,34 @@ async fn main() -> io::Result<()> {
 
                 Ok(())
             }
+
+            BankCommands::Import {
+                file,
+                publisher,
+                name,
+                description,
+            } => {
+                if let Err(e) =
+                    bank_builder::import_bank(&cwd, &file, publisher, name, description).await
+                {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
+
+            BankCommands::Export { id, format, out } => {
+                let res = tokio::task::spawn_blocking(move || {
+                    bank_builder::export_bank(&cwd, &id, &format, out.as_deref())
+                })
+                .await
+                .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                if let Err(e) = res {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
         },
 
         Commands::Plugin { command } => match command {
@@ -240,7 +431,24 @@ async fn main() -> io::Result<()> {
                 path,
                 release,
                 require_signature,
+                jobs,
+                watch,
             } => {
+                if watch {
+                    let res = tokio::task::spawn_blocking(move || {
+                        plugin_builder::watch_plugins(
+                            path.as_deref(),
+                            &release,
+                            &cwd,
+                            require_signature,
+                            resolve_jobs(jobs),
+                        )
+                    })
+                    .await
+                    .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                    return res.map_err(io::Error::other);
+                }
+
                 match path {
                     Some(p) => {
                         let cwd_clone = cwd.clone();
@@ -261,7 +469,12 @@ async fn main() -> io::Result<()> {
                         let rel = release;
                         let req_sig = require_signature;
                         let res = tokio::task::spawn_blocking(move || {
-                            plugin_builder::build_all_plugins(&rel, &cwd_clone, req_sig)
+                            plugin_builder::build_all_plugins(
+                                &rel,
+                                &cwd_clone,
+                                req_sig,
+                                resolve_jobs(jobs),
+                            )
                         })
                         .await
                         .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
@@ -273,6 +486,81 @@ async fn main() -> io::Result<()> {
 
                 Ok(())
             }
+            PluginCommands::Lint { path, format } => {
+                if let Err(e) = plugin_builder::lint_plugins(path.as_deref(), &cwd, &format) {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
+            PluginCommands::Render {
+                id,
+                function,
+                args,
+                input,
+                signal,
+                freq,
+                duration,
+                sample_rate,
+                out,
+            } => {
+                let options = plugin_builder::RenderOptions {
+                    function,
+                    args,
+                    input,
+                    signal,
+                    freq,
+                    duration,
+                    sample_rate,
+                    out,
+                };
+                let res = tokio::task::spawn_blocking(move || {
+                    plugin_builder::render_plugin(&cwd, &id, &options)
+                })
+                .await
+                .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                if let Err(e) = res {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
+            PluginCommands::Bench {
+                id,
+                function,
+                args,
+                iterations,
+                max_load,
+            } => {
+                let options = plugin_builder::BenchOptions {
+                    function,
+                    args,
+                    iterations,
+                    max_load,
+                };
+                let res = tokio::task::spawn_blocking(move || {
+                    plugin_builder::bench_plugin(&cwd, &id, &options)
+                })
+                .await
+                .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                if let Err(e) = res {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
+            PluginCommands::Test { path, update } => {
+                let res = tokio::task::spawn_blocking(move || {
+                    plugin_builder::test_plugins(path.as_deref(), &cwd, update)
+                })
+                .await
+                .map_err(|e| io::Error::other(format!("Join error: {}", e)))?;
+                if let Err(e) = res {
+                    return Err(io::Error::other(e));
+                }
+
+                Ok(())
+            }
             PluginCommands::List {} => {
                 if let Err(e) = addon::plugin::manage::list_plugins(&cwd) {
                     eprintln!("Error listing plugins: {}", e);
@@ -315,3 +603,14 @@ async fn main() -> io::Result<()> {
         },
     }
 }
+
+/// Turns the `--jobs` option into a thread count; 0 means one per available CPU.
+fn resolve_jobs(jobs: usize) -> usize {
+    if jobs == 0 {
+        std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    } else {
+        jobs
+    }
+}
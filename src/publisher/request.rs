@@ -103,21 +103,18 @@ pub async fn get_user_publishers() -> Result<Vec<PublisherInfo>, String> {
                                                 }
 
                                                 // Case 2: tags is a JSON string containing a JSON array like "[\"a\",\"b\"]"
-                                                if let Some(s) = tags_val.as_str() {
-                                                    if let Ok(parsed) =
+                                                if let Some(s) = tags_val.as_str()
+                                                    && let Ok(parsed) =
                                                         serde_json::from_str::<serde_json::Value>(s)
-                                                    {
-                                                        if let Some(arr2) = parsed.as_array() {
-                                                            return Some(
-                                                                arr2.iter()
-                                                                    .filter_map(|t| {
-                                                                        t.as_str()
-                                                                            .map(|s| s.to_string())
-                                                                    })
-                                                                    .collect(),
-                                                            );
-                                                        }
-                                                    }
+                                                    && let Some(arr2) = parsed.as_array()
+                                                {
+                                                    return Some(
+                                                        arr2.iter()
+                                                            .filter_map(|t| {
+                                                                t.as_str().map(|s| s.to_string())
+                                                            })
+                                                            .collect(),
+                                                    );
                                                 }
                                             }
                                             None
@@ -1,18 +1,28 @@
+/// Converts a name to lowercase kebab-case: ASCII letters and digits, with `-` in
+/// place of spaces, punctuation and camel-case boundaries (`My Piano` → `my-piano`,
+/// `MyCo` → `my-co`).
+///
+/// ### Parameters
+/// - `s`: The name to convert
+///
 pub fn to_kebab_case(s: &str) -> String {
     let mut out = String::new();
+    let mut after_lower = false;
 
-    for (i, c) in s.chars().enumerate() {
-        if c.is_uppercase() {
-            if i > 0 {
+    for c in s.chars() {
+        if c.is_ascii_alphanumeric() {
+            if c.is_ascii_uppercase() && after_lower {
                 out.push('-');
             }
-            for lower in c.to_lowercase() {
-                out.push(lower);
-            }
+            out.push(c.to_ascii_lowercase());
+            after_lower = !c.is_ascii_uppercase();
         } else {
-            out.push(c);
+            if !out.is_empty() && !out.ends_with('-') {
+                out.push('-');
+            }
+            after_lower = false;
         }
     }
 
-    out
+    out.trim_end_matches('-').to_string()
 }
@@ -1,4 +1,6 @@
 #[cfg(feature = "cli")]
+use crate::utils::progress;
+#[cfg(feature = "cli")]
 use crossterm::style::{Attribute, Color, ResetColor, SetAttribute, SetForegroundColor};
 #[cfg(feature = "cli")]
 use std::fmt::Write;
@@ -39,12 +41,12 @@ impl Logger {
     #[cfg(feature = "cli")]
     pub fn log_message(&self, level: LogLevel, message: &str) {
         let formatted_status = self.format_status(level);
-        println!(
+        progress::println(&format!(
             "⚒️  {} {} {}",
             self.language_signature(),
             formatted_status,
             message
-        );
+        ));
     }
 
     #[cfg(not(feature = "cli"))]
@@ -55,14 +57,14 @@ impl Logger {
     #[cfg(feature = "cli")]
     pub fn log_message_with_trace(&self, level: LogLevel, message: &str, trace: Vec<&str>) {
         let formatted_status = self.format_status(level);
-        println!(
+        progress::println(&format!(
             "⚒️  {} {} {}",
             self.language_signature(),
             formatted_status,
             message
-        );
+        ));
         for t in trace {
-            println!("     ↳ {}", t);
+            progress::println(&format!("     ↳ {}", t));
         }
     }
 
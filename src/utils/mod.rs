@@ -4,6 +4,7 @@ pub mod fs;
 pub mod kebab_case;
 pub mod logger;
 pub mod path;
+pub mod progress;
 pub mod semver;
 pub mod signature;
 pub mod signing;
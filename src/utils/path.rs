@@ -55,21 +55,21 @@ pub fn get_package_root() -> Option<PathBuf> {
     // At runtime (packaged binary) try to infer the package root from the
     // binary location: walk upward from the running executable and look for
     // common markers (package.json, project-version.json, Cargo.toml).
-    if let Ok(exe_path) = env::current_exe() {
-        if let Some(mut dir) = exe_path.parent().map(|p| p.to_path_buf()) {
-            loop {
-                if dir.join("package.json").exists()
-                    || dir.join("project-version.json").exists()
-                    || dir.join("Cargo.toml").exists()
-                {
-                    return Some(dir);
-                }
-
-                if let Some(parent) = dir.parent() {
-                    dir = parent.to_path_buf();
-                } else {
-                    break;
-                }
+    if let Ok(exe_path) = env::current_exe()
+        && let Some(mut dir) = exe_path.parent().map(|p| p.to_path_buf())
+    {
+        loop {
+            if dir.join("package.json").exists()
+                || dir.join("project-version.json").exists()
+                || dir.join("Cargo.toml").exists()
+            {
+                return Some(dir);
+            }
+
+            if let Some(parent) = dir.parent() {
+                dir = parent.to_path_buf();
+            } else {
+                break;
             }
         }
     }
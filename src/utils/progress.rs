@@ -0,0 +1,177 @@
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::cell::RefCell;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+thread_local! {
+    /// Addon built by the current worker thread, when building in parallel.
+    static CURRENT_JOB: RefCell<Option<JobSlot>> = const { RefCell::new(None) };
+}
+
+struct JobSlot {
+    bar: ProgressBar,
+    /// Output of the build, replayed once it finished
+    lines: Vec<String>,
+}
+
+/// Outcome of one addon built by [`run_jobs`].
+pub struct JobOutcome<R> {
+    pub label: String,
+    pub result: Result<R, String>,
+    pub elapsed: Duration,
+}
+
+/// Builds addons on up to `jobs` threads, with one progress line per running addon.
+///
+/// While an addon builds, its spinner steps update its progress line and its log
+/// output is held back, then printed in one block when it finishes, so the output
+/// of concurrent builds never interleaves. With `jobs` <= 1, addons are built one
+/// after the other with their usual output.
+///
+/// Outcomes are returned in the order of `items`.
+///
+/// ### Parameters
+/// - `items`: The addons to build
+/// - `jobs`: The maximum number of addons built at once
+/// - `label`: Returns the name shown for an addon
+/// - `build`: Builds an addon
+///
+pub fn run_jobs<T, R, L, F>(items: &[T], jobs: usize, label: L, build: F) -> Vec<JobOutcome<R>>
+where
+    T: Sync,
+    R: Send,
+    L: Fn(&T) -> String + Sync,
+    F: Fn(&T) -> Result<R, String> + Sync,
+{
+    if jobs <= 1 {
+        return items
+            .iter()
+            .map(|item| {
+                let start = Instant::now();
+                let result = build(item);
+                JobOutcome {
+                    label: label(item),
+                    result,
+                    elapsed: start.elapsed(),
+                }
+            })
+            .collect();
+    }
+
+    let multi = MultiProgress::new();
+    let style = ProgressStyle::with_template("{spinner} {prefix:.bold} {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner())
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏", "✔"]);
+    let next = AtomicUsize::new(0);
+    let outcomes: Mutex<Vec<Option<JobOutcome<R>>>> =
+        Mutex::new((0..items.len()).map(|_| None).collect());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(items.len()) {
+            scope.spawn(|| {
+                loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(item) = items.get(i) else {
+                        break;
+                    };
+                    let name = label(item);
+                    let bar = multi.add(ProgressBar::new_spinner());
+                    bar.set_style(style.clone());
+                    bar.set_prefix(name.clone());
+                    bar.set_message("starting");
+                    bar.enable_steady_tick(Duration::from_millis(80));
+                    CURRENT_JOB.with(|job| {
+                        *job.borrow_mut() = Some(JobSlot {
+                            bar: bar.clone(),
+                            lines: Vec::new(),
+                        })
+                    });
+
+                    let start = Instant::now();
+                    let result = build(item);
+                    let elapsed = start.elapsed();
+
+                    let lines = CURRENT_JOB
+                        .with(|job| job.borrow_mut().take())
+                        .map(|slot| slot.lines)
+                        .unwrap_or_default();
+                    bar.finish_and_clear();
+                    multi.remove(&bar);
+                    multi.suspend(|| {
+                        let status = if result.is_ok() { "✅" } else { "❌" };
+                        println!("{} {} ({:.1}s)", status, name, elapsed.as_secs_f64());
+                        for line in &lines {
+                            println!("   {}", line);
+                        }
+                    });
+
+                    if let Ok(mut all) = outcomes.lock() {
+                        all[i] = Some(JobOutcome {
+                            label: name,
+                            result,
+                            elapsed,
+                        });
+                    }
+                }
+            });
+        }
+    });
+
+    outcomes
+        .into_inner()
+        .unwrap_or_default()
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
+/// Returns true if the current thread builds an addon in parallel with others.
+pub fn in_job() -> bool {
+    CURRENT_JOB.with(|job| job.borrow().is_some())
+}
+
+/// Shows a step on the progress line of the addon built by the current thread.
+///
+/// Returns false when no addon is built in parallel on this thread.
+///
+/// ### Parameters
+/// - `message`: The step description
+///
+pub fn set_step(message: &str) -> bool {
+    CURRENT_JOB.with(|job| match job.borrow().as_ref() {
+        Some(slot) => {
+            slot.bar.set_message(message.to_string());
+            true
+        }
+        None => false,
+    })
+}
+
+/// Holds back a line of output of the addon built by the current thread.
+///
+/// Returns false when no addon is built in parallel on this thread.
+///
+/// ### Parameters
+/// - `line`: The output line
+///
+pub fn capture_line(line: &str) -> bool {
+    CURRENT_JOB.with(|job| match job.borrow_mut().as_mut() {
+        Some(slot) => {
+            slot.lines.push(line.to_string());
+            true
+        }
+        None => false,
+    })
+}
+
+/// Prints a line, or holds it back when the current thread builds an addon in parallel.
+///
+/// ### Parameters
+/// - `line`: The output line
+///
+pub fn println(line: &str) {
+    if !capture_line(line) {
+        println!("{}", line);
+    }
+}
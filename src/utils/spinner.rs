@@ -1,4 +1,5 @@
 use crate::utils::logger::{LogLevel, Logger};
+use crate::utils::progress;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::cell::Cell;
 use std::time::Duration;
@@ -6,27 +7,43 @@ use std::time::Duration;
 pub struct Spinner {
     bar: ProgressBar,
     active: Cell<bool>,
+    /// Shown on the progress line of a parallel addon build instead of its own line
+    attached: bool,
 }
 
 impl Spinner {
     pub fn new(message: impl Into<String>) -> Self {
+        let message = message.into();
+        if progress::set_step(&message) {
+            return Spinner {
+                bar: ProgressBar::hidden(),
+                active: Cell::new(true),
+                attached: true,
+            };
+        }
+
         let bar = ProgressBar::new_spinner();
         let style = ProgressStyle::with_template("{spinner} {msg}")
             .unwrap_or_else(|_| ProgressStyle::default_spinner())
             .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]);
         bar.set_style(style);
-        bar.set_message(message.into());
+        bar.set_message(message);
         bar.enable_steady_tick(Duration::from_millis(80));
 
         Spinner {
             bar,
             active: Cell::new(true),
+            attached: false,
         }
     }
 
     #[allow(dead_code)]
     pub fn set_message(&self, message: impl Into<String>) {
-        self.bar.set_message(message.into());
+        if self.attached {
+            progress::set_step(&message.into());
+        } else {
+            self.bar.set_message(message.into());
+        }
     }
     #[allow(dead_code)]
     pub fn set_message_allow_dead(&self, message: impl Into<String>) {
@@ -41,17 +58,23 @@ impl Spinner {
     pub fn succeed(&self, message: impl Into<String>) {
         if self.active.get() {
             // Clear spinner then emit a structured success log via Logger
+            // Step successes of parallel builds are summed up by the addon result
             self.bar.finish_and_clear();
-            Logger::new().log_message(LogLevel::Success, &message.into().to_string());
+            if !self.attached {
+                Logger::new().log_message(LogLevel::Success, &message.into().to_string());
+            }
             self.active.set(false);
         }
     }
 
     pub fn fail(&self, message: impl Into<String>) {
         if self.active.get() {
-            // Clear spinner then emit a structured error log via Logger
+            // Clear spinner then emit a structured error log via Logger; parallel
+            // builds report the error in their summary
             self.bar.finish_and_clear();
-            Logger::new().log_message(LogLevel::Error, &message.into().to_string());
+            if !self.attached {
+                Logger::new().log_message(LogLevel::Error, &message.into().to_string());
+            }
             self.active.set(false);
         }
     }
@@ -7,39 +7,35 @@ use crate::utils::{path::get_package_root, signature::get_signature};
 /// 4. compile-time env!("CARGO_PKG_VERSION") as a last resort
 pub fn get_version() -> String {
     // 1) env override
-    if let Ok(v) = std::env::var("DEVAFORGE_CLI_VERSION") {
-        if !v.trim().is_empty() {
-            return v;
-        }
+    if let Ok(v) = std::env::var("DEVAFORGE_CLI_VERSION")
+        && !v.trim().is_empty()
+    {
+        return v;
     }
 
     // 2) project-version.json next to binary
-    if let Ok(exe) = std::env::current_exe() {
-        if let Some(bin_dir) = exe.parent() {
-            let pv = bin_dir.join("project-version.json");
-            if pv.exists() {
-                if let Ok(contents) = std::fs::read_to_string(pv) {
-                    if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&contents) {
-                        if let Some(v) = parsed.get("version").and_then(|s| s.as_str()) {
-                            return v.to_string();
-                        }
-                    }
-                }
-            }
+    if let Ok(exe) = std::env::current_exe()
+        && let Some(bin_dir) = exe.parent()
+    {
+        let pv = bin_dir.join("project-version.json");
+        if pv.exists()
+            && let Ok(contents) = std::fs::read_to_string(pv)
+            && let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&contents)
+            && let Some(v) = parsed.get("version").and_then(|s| s.as_str())
+        {
+            return v.to_string();
         }
     }
 
     // 3) package.json via package root
     if let Some(root) = get_package_root() {
         let pkg = root.join("package.json");
-        if pkg.exists() {
-            if let Ok(contents) = std::fs::read_to_string(pkg) {
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&contents) {
-                    if let Some(v) = parsed.get("version").and_then(|s| s.as_str()) {
-                        return v.to_string();
-                    }
-                }
-            }
+        if pkg.exists()
+            && let Ok(contents) = std::fs::read_to_string(pkg)
+            && let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&contents)
+            && let Some(v) = parsed.get("version").and_then(|s| s.as_str())
+        {
+            return v.to_string();
         }
     }
 